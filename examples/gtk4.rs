@@ -0,0 +1,110 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![allow(unused)]
+
+#[cfg(target_os = "linux")]
+fn main() {
+    use gtk4::prelude::*;
+    use muda::{
+        accelerator::{Accelerator, Code, Modifiers},
+        dpi::{LogicalPosition, Position},
+        AboutMetadata, CheckMenuItem, ContextMenu, Menu, MenuEvent, MenuItem, PredefinedMenuItem,
+        Submenu,
+    };
+
+    let app = gtk4::Application::builder()
+        .application_id("app.tauri.muda.gtk4")
+        .build();
+
+    app.connect_activate(|app| {
+        let window = gtk4::ApplicationWindow::new(app);
+        window.set_default_size(600, 400);
+        let vbox = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+        window.set_child(Some(&vbox));
+
+        let menu_bar = Menu::new();
+        let file_m = Submenu::with_items(
+            "&File",
+            true,
+            &[
+                &MenuItem::new(
+                    "&New",
+                    true,
+                    Some(Accelerator::new(Some(Modifiers::CONTROL), Code::KeyN)),
+                ),
+                &CheckMenuItem::new("&Word Wrap", true, true, None),
+                &PredefinedMenuItem::separator(),
+                &PredefinedMenuItem::quit(None),
+            ],
+        )
+        .unwrap();
+        let edit_m = Submenu::with_items(
+            "&Edit",
+            true,
+            &[
+                &PredefinedMenuItem::undo(None),
+                &PredefinedMenuItem::redo(None),
+                &PredefinedMenuItem::separator(),
+                &PredefinedMenuItem::cut(None),
+                &PredefinedMenuItem::copy(None),
+                &PredefinedMenuItem::paste(None),
+                &PredefinedMenuItem::select_all(None),
+            ],
+        )
+        .unwrap();
+        let help_m = Submenu::with_items(
+            "&Help",
+            true,
+            &[&PredefinedMenuItem::about(
+                None,
+                Some(AboutMetadata {
+                    name: Some("muda gtk4 example".to_string()),
+                    ..Default::default()
+                }),
+            )],
+        )
+        .unwrap();
+        menu_bar.append_items(&[&file_m, &edit_m, &help_m]).unwrap();
+        menu_bar.init_for_gtk4_window(&window, Some(&vbox)).unwrap();
+
+        let entry = gtk4::TextView::new();
+        entry.set_vexpand(true);
+        vbox.append(&entry);
+
+        // show the edit menu as a context menu on right click
+        let gesture = gtk4::GestureClick::builder().button(3).build();
+        let window_ = window.clone();
+        gesture.connect_pressed(move |gesture, _, x, y| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            let position = Position::Logical(LogicalPosition::new(x, y));
+            edit_m.show_context_menu_for_gtk4_window(window_.upcast_ref(), Some(position));
+        });
+        entry.add_controller(gesture);
+
+        window.present();
+
+        // keep the menu alive as long as the window
+        unsafe { window.set_data("muda-menu", menu_bar) };
+    });
+
+    glib_timeout(|| {
+        while let Ok(event) = MenuEvent::receiver().try_recv() {
+            println!("{event:?}");
+        }
+    });
+
+    app.run();
+}
+
+#[cfg(target_os = "linux")]
+fn glib_timeout(f: impl Fn() + 'static) {
+    gtk4::glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
+        f();
+        gtk4::glib::ControlFlow::Continue
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {}
@@ -47,12 +47,38 @@ pub struct AboutMetadata {
     ///
     /// - **macOS:** Unsupported.
     pub website_label: Option<String>,
+    /// The license kind of the application, shown as a link to the license text.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Takes precedence over `license`.
+    /// - **Windows / macOS:** Unsupported.
+    pub license_kind: Option<LicenseKind>,
     /// The credits.
     ///
     /// ## Platform-specific
     ///
-    /// - **Windows / Linux:** Unsupported.
+    /// - **Linux:** Shown as a "Credits" section, one line per entry.
+    /// - **Windows:** Unsupported.
     pub credits: Option<String>,
+    /// The artists of the application.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub artists: Option<Vec<String>>,
+    /// The documenters of the application.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub documenters: Option<Vec<String>>,
+    /// The translators of the application, one per line.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub translators: Option<String>,
     /// The application icon.
     ///
     /// ## Platform-specific
@@ -61,7 +87,69 @@ pub struct AboutMetadata {
     pub icon: Option<Icon>,
 }
 
+/// A well-known license for [`AboutMetadata::license_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LicenseKind {
+    /// The GNU General Public License, version 2.0 or later.
+    Gpl20,
+    /// The GNU General Public License, version 3.0 or later.
+    Gpl30,
+    /// The GNU Lesser General Public License, version 2.1 or later.
+    Lgpl21,
+    /// The GNU Lesser General Public License, version 3.0 or later.
+    Lgpl30,
+    /// The BSD standard license.
+    Bsd,
+    /// The MIT/X11 standard license.
+    MitX11,
+    /// The Artistic License, version 2.0.
+    Artistic,
+    /// The GNU General Public License, version 2.0 only.
+    Gpl20Only,
+    /// The GNU General Public License, version 3.0 only.
+    Gpl30Only,
+    /// The GNU Lesser General Public License, version 2.1 only.
+    Lgpl21Only,
+    /// The GNU Lesser General Public License, version 3.0 only.
+    Lgpl30Only,
+    /// The GNU Affero General Public License, version 3.0 or later.
+    Agpl30,
+    /// The GNU Affero General Public License, version 3.0 only.
+    Agpl30Only,
+    /// The 3-clause BSD license.
+    Bsd3,
+    /// The Apache License, version 2.0.
+    Apache20,
+    /// The Mozilla Public License, version 2.0.
+    Mpl20,
+}
+
 impl AboutMetadata {
+    /// Sets a function creating the dialog shown by [`PredefinedMenuItem::about`](crate::PredefinedMenuItem::about)
+    /// items, instead of the default [`gtk::AboutDialog`].
+    ///
+    /// The returned window is made transient for the window owning the menu
+    /// the item was activated from, if any, and shown. The function is called
+    /// on the GTK main thread and is only used by items activated on the thread
+    /// it was set on.
+    #[cfg(target_os = "linux")]
+    pub fn set_gtk_dialog_handler<F: Fn(&AboutMetadata) -> gtk::Window + 'static>(f: Option<F>) {
+        crate::platform_impl::set_about_dialog_handler(f.map(|f| Box::new(f) as _));
+    }
+
+    /// Sets a function creating the dialog shown by [`PredefinedMenuItem::about`](crate::PredefinedMenuItem::about)
+    /// items activated from GTK4 menus, instead of the default [`gtk4::AboutDialog`].
+    ///
+    /// The returned window is made transient for the window owning the menu
+    /// the item was activated from, if any, and presented. The function is called
+    /// on the GTK main thread and is only used by items activated on the thread
+    /// it was set on.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn set_gtk4_dialog_handler<F: Fn(&AboutMetadata) -> gtk4::Window + 'static>(f: Option<F>) {
+        crate::platform_impl::gtk4::set_about_dialog_handler(f.map(|f| Box::new(f) as _));
+    }
+
     #[allow(unused)]
     pub(crate) fn full_version(&self) -> Option<String> {
         Some(format!(
@@ -212,15 +300,53 @@ impl AboutMetadataBuilder {
         self.0.website_label = website_label.map(|s| s.into());
         self
     }
+    /// Sets the license kind of the application, shown as a link to the license text.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Takes precedence over `license`.
+    /// - **Windows / macOS:** Unsupported.
+    pub fn license_kind(mut self, license_kind: Option<LicenseKind>) -> Self {
+        self.0.license_kind = license_kind;
+        self
+    }
     /// Sets the credits.
     ///
     /// ## Platform-specific
     ///
-    /// - **Windows / Linux:** Unsupported.
+    /// - **Linux:** Shown as a "Credits" section, one line per entry.
+    /// - **Windows:** Unsupported.
     pub fn credits<S: Into<String>>(mut self, credits: Option<S>) -> Self {
         self.0.credits = credits.map(|s| s.into());
         self
     }
+    /// Sets the artists of the application.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn artists(mut self, artists: Option<Vec<String>>) -> Self {
+        self.0.artists = artists;
+        self
+    }
+    /// Sets the documenters of the application.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn documenters(mut self, documenters: Option<Vec<String>>) -> Self {
+        self.0.documenters = documenters;
+        self
+    }
+    /// Sets the translators of the application, one per line.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn translators<S: Into<String>>(mut self, translators: Option<S>) -> Self {
+        self.0.translators = translators.map(|s| s.into());
+        self
+    }
     /// Sets the application icon.
     ///
     /// ## Platform-specific
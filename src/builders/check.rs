@@ -12,6 +12,7 @@ pub struct CheckMenuItemBuilder {
     checked: bool,
     accelerator: Option<Accelerator>,
     id: Option<MenuId>,
+    tooltip: Option<String>,
 }
 
 impl CheckMenuItemBuilder {
@@ -57,12 +58,22 @@ impl CheckMenuItemBuilder {
         Ok(self)
     }
 
+    /// Set the tooltip for this check menu item.
+    ///
+    /// See [`CheckMenuItem::set_tooltip`] for more info.
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
     /// Build this check menu item.
     pub fn build(self) -> CheckMenuItem {
-        if let Some(id) = self.id {
+        let item = if let Some(id) = self.id {
             CheckMenuItem::with_id(id, self.text, self.enabled, self.checked, self.accelerator)
         } else {
             CheckMenuItem::new(self.text, self.enabled, self.checked, self.accelerator)
-        }
+        };
+        item.set_tooltip(self.tooltip);
+        item
     }
 }
@@ -17,6 +17,7 @@ pub struct IconMenuItemBuilder {
     accelerator: Option<Accelerator>,
     icon: Option<Icon>,
     native_icon: Option<NativeIcon>,
+    tooltip: Option<String>,
 }
 
 impl IconMenuItemBuilder {
@@ -70,9 +71,17 @@ impl IconMenuItemBuilder {
         Ok(self)
     }
 
+    /// Set the tooltip for this icon menu item.
+    ///
+    /// See [`IconMenuItem::set_tooltip`] for more info.
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
     /// Build this icon menu item.
     pub fn build(self) -> IconMenuItem {
-        if let Some(id) = self.id {
+        let item = if let Some(id) = self.id {
             if self.icon.is_some() {
                 IconMenuItem::with_id(id, self.text, self.enabled, self.icon, self.accelerator)
             } else {
@@ -93,6 +102,8 @@ impl IconMenuItemBuilder {
                 self.native_icon,
                 self.accelerator,
             )
-        }
+        };
+        item.set_tooltip(self.tooltip);
+        item
     }
 }
@@ -11,6 +11,7 @@ pub struct MenuItemBuilder {
     enabled: bool,
     id: Option<MenuId>,
     accelerator: Option<Accelerator>,
+    tooltip: Option<String>,
 }
 
 impl MenuItemBuilder {
@@ -50,12 +51,22 @@ impl MenuItemBuilder {
         Ok(self)
     }
 
+    /// Set the tooltip for this menu item.
+    ///
+    /// See [`MenuItem::set_tooltip`] for more info.
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
     /// Build this menu item.
     pub fn build(self) -> MenuItem {
-        if let Some(id) = self.id {
+        let item = if let Some(id) = self.id {
             MenuItem::with_id(id, self.text, self.enabled, self.accelerator)
         } else {
             MenuItem::new(self.text, self.enabled, self.accelerator)
-        }
+        };
+        item.set_tooltip(self.tooltip);
+        item
     }
 }
@@ -11,6 +11,7 @@ pub struct SubmenuBuilder<'a> {
     enabled: bool,
     id: Option<MenuId>,
     items: Vec<&'a dyn IsMenuItem>,
+    tooltip: Option<String>,
 }
 
 impl std::fmt::Debug for SubmenuBuilder<'_> {
@@ -59,12 +60,22 @@ impl<'a> SubmenuBuilder<'a> {
         self
     }
 
+    /// Set the tooltip for this submenu.
+    ///
+    /// See [`Submenu::set_tooltip`] for more info.
+    pub fn tooltip<S: Into<String>>(mut self, tooltip: S) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
     /// Build this menu item.
     pub fn build(self) -> crate::Result<Submenu> {
-        if let Some(id) = self.id {
-            Submenu::with_id_and_items(id, self.text, self.enabled, &self.items)
+        let submenu = if let Some(id) = self.id {
+            Submenu::with_id_and_items(id, self.text, self.enabled, &self.items)?
         } else {
-            Submenu::with_items(self.text, self.enabled, &self.items)
-        }
+            Submenu::with_items(self.text, self.enabled, &self.items)?
+        };
+        submenu.set_tooltip(self.tooltip);
+        Ok(submenu)
     }
 }
@@ -0,0 +1,288 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{menu_index::MenuIndex, menu_parent::MenuParentKind, MenuId, MenuItemKind, MenuParent};
+
+/// The state of the items of a command, returned by the predicates
+/// registered with [`CommandRegistry::register`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandState {
+    /// Whether the items are enabled.
+    pub enabled: bool,
+    /// Whether the check menu items are checked, or `None` to leave them as they are.
+    pub checked: Option<bool>,
+    /// The text of the items, or `None` to leave it as it is.
+    pub text: Option<String>,
+}
+
+impl Default for CommandState {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            checked: None,
+            text: None,
+        }
+    }
+}
+
+impl CommandState {
+    /// Creates a state with the items enabled or disabled.
+    pub fn enabled(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Sets whether the check menu items are checked.
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.checked = Some(checked);
+        self
+    }
+
+    /// Sets the text of the items.
+    pub fn with_text<S: Into<String>>(mut self, text: S) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+}
+
+impl From<bool> for CommandState {
+    fn from(enabled: bool) -> Self {
+        Self::enabled(enabled)
+    }
+}
+
+type Predicate = Rc<dyn Fn() -> CommandState>;
+
+#[derive(Default)]
+struct Registry {
+    roots: Vec<MenuParentKind>,
+    commands: HashMap<MenuId, Predicate>,
+}
+
+/// Keeps the items of menus in sync with the state of the commands they trigger.
+///
+/// Each command is identified by the [`MenuId`] of its items and has a predicate
+/// returning its [`CommandState`]. [`CommandRegistry::refresh`] evaluates every predicate
+/// and applies the state to every item with that id in the menus added with
+/// [`CommandRegistry::add_menu`] and their submenus, whether they are shown
+/// in a menubar or as a context menu.
+///
+/// Cloning a registry returns a handle to the same registry.
+///
+/// ```
+/// # use muda::{CommandRegistry, Menu, MenuItem};
+/// # use std::{cell::Cell, rc::Rc};
+/// let save = MenuItem::with_id("save", "Save", true, None);
+/// let menu = Menu::with_items(&[&save]).unwrap();
+///
+/// let dirty = Rc::new(Cell::new(false));
+/// let registry = CommandRegistry::new();
+/// registry.add_menu(&menu);
+/// let dirty_ = dirty.clone();
+/// registry.register("save", move || dirty_.get());
+///
+/// registry.refresh();
+/// assert!(!save.is_enabled());
+///
+/// dirty.set(true);
+/// registry.refresh();
+/// assert!(save.is_enabled());
+/// ```
+#[derive(Clone, Default)]
+pub struct CommandRegistry {
+    inner: Rc<RefCell<Registry>>,
+}
+
+impl CommandRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a menu or submenu, for example a context menu, whose items are updated
+    /// by [`CommandRegistry::refresh`]. The registry keeps the menu alive
+    /// until it is removed with [`CommandRegistry::remove_menu`].
+    pub fn add_menu<'a>(&self, menu: impl Into<MenuParent<'a>>) {
+        self.inner.borrow_mut().roots.push(menu.into().to_kind());
+    }
+
+    /// Removes a menu or submenu added with [`CommandRegistry::add_menu`],
+    /// its items are left as they are and the registry no longer keeps it alive.
+    pub fn remove_menu<'a>(&self, menu: impl Into<MenuParent<'a>>) {
+        let menu = menu.into();
+        self.inner
+            .borrow_mut()
+            .roots
+            .retain(|root| !root.as_parent().is_same(&menu));
+    }
+
+    /// Registers the predicate of the command `id`, replacing the previous one.
+    ///
+    /// The predicate returns a [`CommandState`], or a `bool` for whether the items are enabled.
+    pub fn register<I, S, F>(&self, id: I, predicate: F)
+    where
+        I: Into<MenuId>,
+        S: Into<CommandState>,
+        F: Fn() -> S + 'static,
+    {
+        self.inner
+            .borrow_mut()
+            .commands
+            .insert(id.into(), Rc::new(move || predicate().into()));
+    }
+
+    /// Removes the predicate of the command `id`, its items are left as they are.
+    pub fn unregister(&self, id: &MenuId) {
+        self.inner.borrow_mut().commands.remove(id);
+    }
+
+    /// Evaluates the predicate of every command and applies
+    /// the result to every item with that id.
+    ///
+    /// Call it when the state of your app changes, or before showing a menu.
+    pub fn refresh(&self) {
+        let items = {
+            let registry = self.inner.borrow();
+            let mut items = Vec::new();
+            for root in &registry.roots {
+                let index = MenuIndex::new(root.as_parent().items());
+                items.extend(
+                    index
+                        .items()
+                        .iter()
+                        .filter_map(|i| Some((i.clone(), registry.commands.get(i.id())?.clone()))),
+                );
+            }
+            items
+        };
+
+        // predicates may use the registry, so it isn't borrowed while they run
+        let mut states = HashMap::new();
+        for (item, predicate) in items {
+            let state = states
+                .entry(item.id().clone())
+                .or_insert_with(|| predicate());
+            apply(&item, state);
+        }
+    }
+}
+
+/// Applies `state` to `item`, leaving what didn't change untouched.
+fn apply(item: &MenuItemKind, state: &CommandState) {
+    match item {
+        MenuItemKind::MenuItem(i) => {
+            if i.is_enabled() != state.enabled {
+                i.set_enabled(state.enabled);
+            }
+        }
+        MenuItemKind::Submenu(i) => {
+            if i.is_enabled() != state.enabled {
+                i.set_enabled(state.enabled);
+            }
+        }
+        MenuItemKind::Check(i) => {
+            if i.is_enabled() != state.enabled {
+                i.set_enabled(state.enabled);
+            }
+            if let Some(checked) = state.checked.filter(|c| *c != i.is_checked()) {
+                i.set_checked(checked);
+            }
+        }
+        MenuItemKind::Icon(i) => {
+            if i.is_enabled() != state.enabled {
+                i.set_enabled(state.enabled);
+            }
+        }
+        MenuItemKind::Predefined(_) => {}
+    }
+
+    if let Some(text) = state.text.as_deref().filter(|t| *t != item_text(item)) {
+        match item {
+            MenuItemKind::MenuItem(i) => i.set_text(text),
+            MenuItemKind::Submenu(i) => i.set_text(text),
+            MenuItemKind::Predefined(i) => i.set_text(text),
+            MenuItemKind::Check(i) => i.set_text(text),
+            MenuItemKind::Icon(i) => i.set_text(text),
+        }
+    }
+}
+
+fn item_text(item: &MenuItemKind) -> String {
+    match item {
+        MenuItemKind::MenuItem(i) => i.text(),
+        MenuItemKind::Submenu(i) => i.text(),
+        MenuItemKind::Predefined(i) => i.text(),
+        MenuItemKind::Check(i) => i.text(),
+        MenuItemKind::Icon(i) => i.text(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{CheckMenuItem, Menu, MenuItem, Submenu};
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn applies_states_to_every_item_with_the_id() {
+        let save = MenuItem::with_id("save", "Save", true, None);
+        let wrap = CheckMenuItem::with_id("wrap", "Wrap", true, false, None);
+        let file = Submenu::with_items("File", true, &[&save, &wrap]).unwrap();
+        let menu = Menu::with_items(&[&file]).unwrap();
+        let context_save = MenuItem::with_id("save", "Save", true, None);
+        let context_menu = Submenu::with_items("", true, &[&context_save]).unwrap();
+
+        let registry = CommandRegistry::new();
+        registry.add_menu(&menu);
+        registry.add_menu(&context_menu);
+
+        let dirty = Rc::new(Cell::new(false));
+        let calls = Rc::new(Cell::new(0));
+        let (dirty_, calls_) = (dirty.clone(), calls.clone());
+        registry.register("save", move || {
+            calls_.set(calls_.get() + 1);
+            dirty_.get()
+        });
+        registry.register("wrap", || {
+            CommandState::enabled(false)
+                .with_checked(true)
+                .with_text("Wrap Lines")
+        });
+
+        registry.refresh();
+        assert!(!save.is_enabled());
+        assert!(!context_save.is_enabled());
+        assert_eq!(calls.get(), 1);
+        assert!(!wrap.is_enabled());
+        assert!(wrap.is_checked());
+        assert_eq!(wrap.text(), "Wrap Lines");
+
+        // items added later are found too
+        let other_save = MenuItem::with_id("save", "Save", false, None);
+        menu.append(&other_save).unwrap();
+        dirty.set(true);
+        registry.refresh();
+        assert!(save.is_enabled());
+        assert!(context_save.is_enabled());
+        assert!(other_save.is_enabled());
+
+        // removed menus are left as they are
+        registry.remove_menu(&context_menu);
+        dirty.set(false);
+        registry.refresh();
+        assert!(!save.is_enabled());
+        assert!(context_save.is_enabled());
+
+        registry.unregister(&MenuId::new("save"));
+        dirty.set(true);
+        registry.refresh();
+        assert!(!save.is_enabled());
+    }
+}
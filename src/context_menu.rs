@@ -0,0 +1,139 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::dpi::{Position, Size};
+
+/// Options for how a context menu is anchored when shown with
+/// `ContextMenu::show_context_menu_for_gtk_window_with_options`
+/// or `ContextMenu::show_context_menu_for_gtk4_window_with_options`.
+///
+/// The default shows the menu at the cursor position, with its top-left corner
+/// at the cursor, flipping, sliding or resizing it as needed to fit on screen.
+#[derive(Debug, Clone, Default)]
+pub struct ContextMenuOptions {
+    /// What the menu is anchored to.
+    pub anchor: ContextMenuAnchor,
+    /// The point of the anchor the menu is aligned to.
+    pub anchor_gravity: ContextMenuGravity,
+    /// The point of the menu aligned to the anchor.
+    pub menu_gravity: ContextMenuGravity,
+    /// How the menu is adjusted when it doesn't fit on screen.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (GTK4 menus)**: Unsupported, popovers always flip and slide to fit.
+    pub anchor_hints: ContextMenuAnchorHints,
+}
+
+impl ContextMenuOptions {
+    /// Creates options showing the menu at `position`, or at the cursor position if `None`.
+    pub fn at(position: Option<Position>) -> Self {
+        Self {
+            anchor: position
+                .map(ContextMenuAnchor::Position)
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the position of the anchor, the top-left corner for [`ContextMenuAnchor::Rect`],
+    /// or `None` for the cursor position and widget anchors.
+    pub fn position(&self) -> Option<Position> {
+        match &self.anchor {
+            ContextMenuAnchor::Position(position) => Some(*position),
+            ContextMenuAnchor::Rect { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+/// What a context menu is anchored to, see [`ContextMenuOptions::anchor`].
+#[derive(Debug, Clone, Default)]
+pub enum ContextMenuAnchor {
+    /// The cursor position.
+    #[default]
+    Cursor,
+    /// A position relative to the window top-left corner.
+    Position(Position),
+    /// A rectangle relative to the window top-left corner, for example the bounds of a button.
+    Rect {
+        /// The rectangle top-left corner.
+        position: Position,
+        /// The rectangle size.
+        size: Size,
+    },
+    /// The allocation of a widget inside the window.
+    ///
+    /// Menus shown in a `gtk4::Window` can't be anchored to it and aren't shown.
+    #[cfg(target_os = "linux")]
+    Widget(gtk::Widget),
+    /// The allocation of a widget inside the window.
+    ///
+    /// Menus shown in a `gtk::Window` can't be anchored to it and aren't shown.
+    #[cfg(feature = "gtk4")]
+    Gtk4Widget(gtk4::Widget),
+}
+
+/// A point of a rectangle, see [`ContextMenuOptions::anchor_gravity`]
+/// and [`ContextMenuOptions::menu_gravity`].
+///
+/// ## Platform-specific:
+///
+/// - **Linux (GTK4 menus)**: Only [`ContextMenuOptions::menu_gravity`] is used
+///   to pick the side of the anchor the menu is shown on, for example
+///   [`ContextMenuGravity::NorthWest`] shows the menu below the anchor
+///   and [`ContextMenuGravity::SouthWest`] above it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ContextMenuGravity {
+    /// The top-left corner.
+    #[default]
+    NorthWest,
+    /// The center of the top edge.
+    North,
+    /// The top-right corner.
+    NorthEast,
+    /// The center of the left edge.
+    West,
+    /// The center.
+    Center,
+    /// The center of the right edge.
+    East,
+    /// The bottom-left corner.
+    SouthWest,
+    /// The center of the bottom edge.
+    South,
+    /// The bottom-right corner.
+    SouthEast,
+}
+
+/// How a context menu is adjusted when it doesn't fit on screen,
+/// see [`ContextMenuOptions::anchor_hints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContextMenuAnchorHints {
+    /// Flip the menu horizontally to the other side of the anchor.
+    pub flip_x: bool,
+    /// Flip the menu vertically to the other side of the anchor.
+    pub flip_y: bool,
+    /// Slide the menu horizontally along the anchor.
+    pub slide_x: bool,
+    /// Slide the menu vertically along the anchor.
+    pub slide_y: bool,
+    /// Shrink the menu width.
+    pub resize_x: bool,
+    /// Shrink the menu height.
+    pub resize_y: bool,
+}
+
+impl Default for ContextMenuAnchorHints {
+    fn default() -> Self {
+        Self {
+            flip_x: true,
+            flip_y: true,
+            slide_x: true,
+            slide_y: true,
+            resize_x: true,
+            resize_y: true,
+        }
+    }
+}
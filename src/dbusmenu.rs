@@ -0,0 +1,44 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use gtk::gio;
+
+use crate::platform_impl::DBusMenuRoot;
+
+/// A menu published over DBus using the `com.canonical.dbusmenu` protocol,
+/// see [`Menu::serve_dbusmenu`](crate::Menu::serve_dbusmenu).
+///
+/// The menu stays in sync with later changes to its items
+/// and is removed from the bus when this is dropped.
+pub struct DBusMenuServer {
+    pub(crate) inner: crate::platform_impl::DBusMenuServer,
+}
+
+impl DBusMenuServer {
+    /// Publishes `root` at `object_path` on `connection`, or on the session bus if `None`.
+    pub(crate) fn serve(
+        root: DBusMenuRoot,
+        connection: Option<&gio::DBusConnection>,
+        object_path: &str,
+    ) -> crate::Result<Self> {
+        let connection = match connection {
+            Some(connection) => connection.clone(),
+            None => gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)?,
+        };
+        Ok(Self {
+            inner: crate::platform_impl::DBusMenuServer::new(root, &connection, object_path)?,
+        })
+    }
+
+    /// Returns the object path this menu is published on.
+    pub fn object_path(&self) -> &str {
+        self.inner.object_path()
+    }
+
+    /// Returns the revision of the menu layout, it is increased
+    /// every time clients are notified that items were added or removed.
+    pub fn revision(&self) -> u32 {
+        self.inner.revision()
+    }
+}
@@ -24,8 +24,15 @@ pub enum Error {
     #[cfg(target_os = "linux")]
     #[error("This menu has already been initialized for this gtk window`")]
     AlreadyInitialized,
+    #[error("An item with the id `{}` already exists in this `Menu`", .0.as_ref())]
+    DuplicateId(crate::MenuId),
+    #[error("A submenu can't be moved into itself or one of its submenus")]
+    SubmenuIntoItself,
     #[error(transparent)]
     AcceleratorParseError(#[from] AcceleratorParseError),
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    #[error(transparent)]
+    DBusError(#[from] gtk::glib::Error),
 }
 
 /// Convenient type alias of Result type for muda.
@@ -5,7 +5,12 @@
 // taken from https://github.com/rust-windowing/winit/blob/92fdf5ba85f920262a61cee4590f4a11ad5738d1/src/icon.rs
 
 use crate::platform_impl::PlatformIcon;
-use std::{error::Error, fmt, io, mem};
+use std::{
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    io, mem,
+};
 
 #[repr(C)]
 #[derive(Debug)]
@@ -32,6 +37,12 @@ pub enum BadIcon {
         width_x_height: usize,
         pixel_count: usize,
     },
+    /// Produced when `width` or `height` is zero.
+    ZeroSize { width: u32, height: u32 },
+    /// Produced when the dimensions are too large to be represented, i.e. when `width`
+    /// or `height` doesn't fit in an `i32`, a row of pixels (`width * 4` bytes) doesn't fit
+    /// in an `i32`, or the total byte count (`width * height * 4`) overflows a `usize`.
+    TooLarge { width: u32, height: u32 },
     /// Produced when underlying OS functionality failed to create the icon
     OsError(io::Error),
 }
@@ -52,6 +63,14 @@ impl fmt::Display for BadIcon {
                 "The specified dimensions ({:?}x{:?}) don't match the number of pixels supplied by the `rgba` argument ({:?}). For those dimensions, the expected pixel count is {:?}.",
                 width, height, pixel_count, width_x_height,
             ),
+            BadIcon::ZeroSize { width, height } => write!(f,
+                "The specified dimensions ({:?}x{:?}) are invalid, an icon must be at least 1x1.",
+                width, height,
+            ),
+            BadIcon::TooLarge { width, height } => write!(f,
+                "The specified dimensions ({:?}x{:?}) are too large to be represented as 32bpp RGBA pixels.",
+                width, height,
+            ),
             BadIcon::OsError(e) => write!(f, "OS error when instantiating the icon: {:?}", e),
         }
     }
@@ -63,7 +82,7 @@ impl Error for BadIcon {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct RgbaIcon {
     pub(crate) rgba: Vec<u8>,
     pub(crate) width: u32,
@@ -85,12 +104,13 @@ mod constructors {
                     byte_count: rgba.len(),
                 });
             }
+            let width_x_height = Self::checked_byte_count(width, height)? / PIXEL_SIZE;
             let pixel_count = rgba.len() / PIXEL_SIZE;
-            if pixel_count != (width * height) as usize {
+            if pixel_count != width_x_height {
                 Err(BadIcon::DimensionsVsPixelCount {
                     width,
                     height,
-                    width_x_height: (width * height) as usize,
+                    width_x_height,
                     pixel_count,
                 })
             } else {
@@ -101,6 +121,22 @@ mod constructors {
                 })
             }
         }
+
+        /// Validates `width` and `height` and returns the number of bytes
+        /// needed to store an icon of these dimensions.
+        pub fn checked_byte_count(width: u32, height: u32) -> Result<usize, BadIcon> {
+            if width == 0 || height == 0 {
+                return Err(BadIcon::ZeroSize { width, height });
+            }
+
+            // gdk-pixbuf and GDI take dimensions and row strides as `i32`
+            let max = i32::MAX as usize;
+            (width as usize)
+                .checked_mul(PIXEL_SIZE)
+                .filter(|row| *row <= max && height as usize <= max)
+                .and_then(|row| row.checked_mul(height as usize))
+                .ok_or(BadIcon::TooLarge { width, height })
+        }
     }
 
     impl NoIcon {
@@ -112,6 +148,287 @@ mod constructors {
     }
 }
 
+/// A corner of an icon, used to position a badge with [`Icon::with_badge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IconCorner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+mod transformations {
+    use super::*;
+
+    impl RgbaIcon {
+        fn pixels(&self) -> impl Iterator<Item = &[u8]> {
+            self.rgba.chunks_exact(PIXEL_SIZE)
+        }
+
+        pub fn to_grayscale(&self) -> RgbaIcon {
+            let rgba = self
+                .pixels()
+                .flat_map(|p| {
+                    let l = luminance(p);
+                    [l, l, l, p[3]]
+                })
+                .collect();
+            RgbaIcon {
+                rgba,
+                width: self.width,
+                height: self.height,
+            }
+        }
+
+        pub fn tinted(&self, color: [u8; 3]) -> RgbaIcon {
+            let rgba = self
+                .pixels()
+                .flat_map(|p| {
+                    let l = luminance(p) as u32;
+                    let tint = |c: u8| ((c as u32 * l + 127) / 255) as u8;
+                    [tint(color[0]), tint(color[1]), tint(color[2]), p[3]]
+                })
+                .collect();
+            RgbaIcon {
+                rgba,
+                width: self.width,
+                height: self.height,
+            }
+        }
+
+        pub fn with_badge(&self, badge: &RgbaIcon, corner: IconCorner) -> RgbaIcon {
+            let badge_width = (self.width / 2).max(1);
+            let badge_height = (self.height / 2).max(1);
+            let badge = badge.resized(badge_width, badge_height);
+
+            let x_offset = match corner {
+                IconCorner::TopLeft | IconCorner::BottomLeft => 0,
+                IconCorner::TopRight | IconCorner::BottomRight => self.width - badge_width,
+            };
+            let y_offset = match corner {
+                IconCorner::TopLeft | IconCorner::TopRight => 0,
+                IconCorner::BottomLeft | IconCorner::BottomRight => self.height - badge_height,
+            };
+
+            let mut rgba = self.rgba.clone();
+            for (i, src) in badge.pixels().enumerate() {
+                let x = x_offset as usize + i % badge_width as usize;
+                let y = y_offset as usize + i / badge_width as usize;
+                let offset = (y * self.width as usize + x) * PIXEL_SIZE;
+                let dst = &mut rgba[offset..offset + PIXEL_SIZE];
+                blend_over(dst, src);
+            }
+
+            RgbaIcon {
+                rgba,
+                width: self.width,
+                height: self.height,
+            }
+        }
+
+        /// Resizes the icon, using an area average when shrinking and bilinear
+        /// interpolation when enlarging. Both passes work on premultiplied alpha
+        /// so transparent pixels don't bleed their color into the result.
+        pub fn resized(&self, width: u32, height: u32) -> RgbaIcon {
+            if width == self.width && height == self.height {
+                return self.clone();
+            }
+
+            let premultiplied = self
+                .pixels()
+                .flat_map(|p| {
+                    let a = p[3] as f32 / 255.0;
+                    [
+                        p[0] as f32 * a,
+                        p[1] as f32 * a,
+                        p[2] as f32 * a,
+                        p[3] as f32,
+                    ]
+                })
+                .collect::<Vec<_>>();
+
+            let horizontal = resample(
+                &premultiplied,
+                self.width as usize,
+                self.height as usize,
+                width as usize,
+                self.width as usize,
+                1,
+            );
+            let vertical = resample(
+                &horizontal,
+                self.height as usize,
+                width as usize,
+                height as usize,
+                1,
+                width as usize,
+            );
+
+            let rgba = (0..(width * height) as usize)
+                .flat_map(|i| {
+                    // `vertical` is laid out column by column
+                    let x = i % width as usize;
+                    let y = i / width as usize;
+                    let p = &vertical[(x * height as usize + y) * 4..][..4];
+                    let a = p[3];
+                    let unpremultiply = |c: f32| {
+                        if a > 0.0 {
+                            (c * 255.0 / a).round().clamp(0.0, 255.0) as u8
+                        } else {
+                            0
+                        }
+                    };
+                    [
+                        unpremultiply(p[0]),
+                        unpremultiply(p[1]),
+                        unpremultiply(p[2]),
+                        a.round().clamp(0.0, 255.0) as u8,
+                    ]
+                })
+                .collect();
+
+            RgbaIcon {
+                rgba,
+                width,
+                height,
+            }
+        }
+
+        /// Draws a `size` x `size` ring, filled clockwise from the top up to `progress`
+        /// (clamped to `0.0..=1.0`), the rest of the ring is drawn as a faint track.
+        pub fn progress_ring(progress: f32, size: u32, color: [u8; 3]) -> RgbaIcon {
+            let progress = if progress.is_nan() {
+                0.0
+            } else {
+                progress.clamp(0.0, 1.0)
+            };
+            let center = size as f32 / 2.0;
+            let thickness = (size as f32 / 8.0).max(1.0);
+            let radius = center - thickness / 2.0 - 0.5;
+
+            let mut rgba = Vec::with_capacity(size as usize * size as usize * PIXEL_SIZE);
+            for y in 0..size {
+                for x in 0..size {
+                    let dx = x as f32 + 0.5 - center;
+                    let dy = y as f32 + 0.5 - center;
+
+                    // 1px wide anti-aliased edges on both sides of the ring
+                    let distance = ((dx * dx + dy * dy).sqrt() - radius).abs();
+                    let coverage = (thickness / 2.0 + 0.5 - distance).clamp(0.0, 1.0);
+
+                    // angle from the top, going clockwise, in `0.0..1.0`
+                    let angle = dx.atan2(-dy) / std::f32::consts::TAU;
+                    let angle = if angle < 0.0 { angle + 1.0 } else { angle };
+                    let opacity = if angle < progress { 1.0 } else { 0.25 };
+
+                    let [r, g, b] = color;
+                    rgba.extend([r, g, b, (coverage * opacity * 255.0).round() as u8]);
+                }
+            }
+
+            RgbaIcon {
+                rgba,
+                width: size,
+                height: size,
+            }
+        }
+    }
+
+    /// Rec. 601 luma of a pixel.
+    fn luminance(p: &[u8]) -> u8 {
+        ((p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114 + 500) / 1000) as u8
+    }
+
+    /// Composites `src` over `dst` using the "source over" operator.
+    fn blend_over(dst: &mut [u8], src: &[u8]) {
+        let src_a = src[3] as f32 / 255.0;
+        let dst_a = dst[3] as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            dst.copy_from_slice(&[0, 0, 0, 0]);
+            return;
+        }
+        for c in 0..3 {
+            let value = (src[c] as f32 * src_a + dst[c] as f32 * dst_a * (1.0 - src_a)) / out_a;
+            dst[c] = value.round().clamp(0.0, 255.0) as u8;
+        }
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+
+    /// Resamples `lines` lines of `src_len` 4-channel pixels each to `dst_len` pixels.
+    ///
+    /// Pixel `i` of line `l` is read from `src[(l * line_stride + i * pixel_stride) * 4]`.
+    /// The output is written line after line, so resampling rows with
+    /// `pixel_stride == 1` and then the result with `line_stride == 1`
+    /// produces a column-major image.
+    fn resample(
+        src: &[f32],
+        src_len: usize,
+        lines: usize,
+        dst_len: usize,
+        line_stride: usize,
+        pixel_stride: usize,
+    ) -> Vec<f32> {
+        let scale = src_len as f32 / dst_len as f32;
+        let mut out = Vec::with_capacity(lines * dst_len * 4);
+
+        for line in 0..lines {
+            let pixel = |i: usize| {
+                let offset = (line * line_stride + i * pixel_stride) * 4;
+                &src[offset..offset + 4]
+            };
+
+            for i in 0..dst_len {
+                let mut acc = [0.0f32; 4];
+                if scale > 1.0 {
+                    // area average of all source pixels covered by this one
+                    let start = i as f32 * scale;
+                    let end = start + scale;
+                    let mut j = start.floor() as usize;
+                    while (j as f32) < end && j < src_len {
+                        let weight = (end.min(j as f32 + 1.0) - start.max(j as f32)) / scale;
+                        for (a, c) in acc.iter_mut().zip(pixel(j)) {
+                            *a += c * weight;
+                        }
+                        j += 1;
+                    }
+                } else {
+                    let center = ((i as f32 + 0.5) * scale - 0.5).max(0.0);
+                    let j0 = (center.floor() as usize).min(src_len - 1);
+                    let j1 = (j0 + 1).min(src_len - 1);
+                    let t = center - j0 as f32;
+                    for ((a, c0), c1) in acc.iter_mut().zip(pixel(j0)).zip(pixel(j1)) {
+                        *a = c0 * (1.0 - t) + c1 * t;
+                    }
+                }
+                out.extend_from_slice(&acc);
+            }
+        }
+
+        out
+    }
+}
+
+/// Statistics of the cache of native images created for icon menu items,
+/// returned by [`Icon::cache_stats`].
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IconCacheStats {
+    /// The number of cached pixbufs, or textures for GTK4 menus.
+    pub entries: usize,
+    /// The total size in bytes of the pixel data of the cached pixbufs.
+    pub bytes: usize,
+    /// The number of times a cached pixbuf was reused.
+    pub hits: u64,
+    /// The number of times a pixbuf had to be created.
+    pub misses: u64,
+}
+
 /// An icon used for the window titlebar, taskbar, etc.
 #[derive(Clone)]
 pub struct Icon {
@@ -124,6 +441,21 @@ impl fmt::Debug for Icon {
     }
 }
 
+/// Icons are compared by their dimensions and pixel data.
+impl PartialEq for Icon {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.rgba_icon() == other.inner.rgba_icon()
+    }
+}
+
+impl Eq for Icon {}
+
+impl Hash for Icon {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.rgba_icon().hash(state)
+    }
+}
+
 impl Icon {
     /// Creates an icon from 32bpp RGBA data.
     ///
@@ -135,6 +467,118 @@ impl Icon {
         })
     }
 
+    /// Returns the width of this icon in pixels.
+    pub fn width(&self) -> u32 {
+        self.inner.rgba_icon().width
+    }
+
+    /// Returns the height of this icon in pixels.
+    pub fn height(&self) -> u32 {
+        self.inner.rgba_icon().height
+    }
+
+    /// Returns the 32bpp RGBA data of this icon, row by row from the top-left corner.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows:** For icons created with `Icon::from_path` or `Icon::from_resource`,
+    ///   the pixels are read back from the icon loaded by the OS the first time they are needed.
+    ///   Monochrome icons are read back as black and white, and icons that can't be read back
+    ///   are returned as a single transparent pixel.
+    pub fn rgba(&self) -> &[u8] {
+        &self.inner.rgba_icon().rgba
+    }
+
+    /// Converts this icon into its 32bpp RGBA data.
+    ///
+    /// See [`Icon::rgba`] for more info.
+    pub fn into_rgba(self) -> Vec<u8> {
+        self.inner.into_rgba_icon().rgba
+    }
+
+    /// Returns a grayscale copy of this icon, useful to represent a disabled state.
+    pub fn to_grayscale(&self) -> Result<Self, BadIcon> {
+        Self::from_rgba_icon(self.inner.rgba_icon().to_grayscale())
+    }
+
+    /// Returns a copy of this icon colorized with `color`, an RGB triple.
+    ///
+    /// The luminance of each pixel is used to scale `color`, the alpha channel is left untouched.
+    pub fn tinted(&self, color: [u8; 3]) -> Result<Self, BadIcon> {
+        Self::from_rgba_icon(self.inner.rgba_icon().tinted(color))
+    }
+
+    /// Returns a copy of this icon with `badge` drawn over the specified `corner`.
+    ///
+    /// The badge is resized to half the width and height of this icon.
+    pub fn with_badge(&self, badge: &Icon, corner: IconCorner) -> Result<Self, BadIcon> {
+        Self::from_rgba_icon(
+            self.inner
+                .rgba_icon()
+                .with_badge(badge.inner.rgba_icon(), corner),
+        )
+    }
+
+    /// Returns a copy of this icon resized to `width` x `height`.
+    ///
+    /// Pixels are averaged when shrinking and bilinearly interpolated when enlarging.
+    pub fn resized(&self, width: u32, height: u32) -> Result<Self, BadIcon> {
+        RgbaIcon::checked_byte_count(width, height)?;
+        Self::from_rgba_icon(self.inner.rgba_icon().resized(width, height))
+    }
+
+    /// Creates a `size` x `size` icon of a ring, filled clockwise from the top
+    /// up to `progress` which is clamped to `0.0..=1.0`.
+    ///
+    /// The unfilled part of the ring is drawn with a lower opacity of `color`, an RGB triple.
+    pub fn progress_ring(progress: f32, size: u32, color: [u8; 3]) -> Result<Self, BadIcon> {
+        RgbaIcon::checked_byte_count(size, size)?;
+        Self::from_rgba_icon(RgbaIcon::progress_ring(progress, size, color))
+    }
+
+    fn from_rgba_icon(icon: RgbaIcon) -> Result<Self, BadIcon> {
+        Self::from_rgba(icon.rgba, icon.width, icon.height)
+    }
+
+    /// Returns statistics of the cache of `Pixbuf`s
+    /// used to display icons in menu items created on the current thread.
+    ///
+    /// Icon menu items with the same icon content and size share a single pixbuf,
+    /// across all the menubars and context menus they are displayed in.
+    /// A cached pixbuf is released once all icons with its content are dropped.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (GTK4 menus)**: The cache holds a `gdk::Texture` per icon content instead,
+    ///   as GTK4 menus scale icons themselves. The returned statistics add up both caches.
+    #[cfg(target_os = "linux")]
+    pub fn cache_stats() -> IconCacheStats {
+        #[cfg(feature = "gtk4")]
+        {
+            let pixbufs = crate::platform_impl::icon_cache_stats();
+            let textures = crate::platform_impl::gtk4::icon_cache_stats();
+            IconCacheStats {
+                entries: pixbufs.entries + textures.entries,
+                bytes: pixbufs.bytes + textures.bytes,
+                hits: pixbufs.hits + textures.hits,
+                misses: pixbufs.misses + textures.misses,
+            }
+        }
+
+        #[cfg(not(feature = "gtk4"))]
+        crate::platform_impl::icon_cache_stats()
+    }
+
+    /// Clears the cache described in [`Icon::cache_stats`], including the hit and miss counters.
+    ///
+    /// Pixbufs still used by menu items stay alive until these items are dropped.
+    #[cfg(target_os = "linux")]
+    pub fn clear_cache() {
+        crate::platform_impl::clear_icon_cache();
+        #[cfg(feature = "gtk4")]
+        crate::platform_impl::gtk4::clear_icon_cache();
+    }
+
     /// Create an icon from a file path.
     ///
     /// Specify `size` to load a specific icon size from the file, or `None` to load the default
@@ -286,3 +730,167 @@ pub enum NativeIcon {
     /// Permissions for guests.
     UserGuest,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transformations_keep_alpha_and_dimensions() {
+        let icon = RgbaIcon::from_rgba(vec![255, 0, 0, 128, 0, 0, 255, 255], 2, 1).unwrap();
+
+        let gray = icon.to_grayscale();
+        assert_eq!(gray.rgba, vec![76, 76, 76, 128, 29, 29, 29, 255]);
+
+        let tinted = icon.tinted([0, 255, 0]);
+        assert_eq!(tinted.rgba, vec![0, 76, 0, 128, 0, 29, 0, 255]);
+
+        let resized = icon.resized(4, 2);
+        assert_eq!((resized.width, resized.height), (4, 2));
+        assert_eq!(resized.rgba.len(), 4 * 2 * PIXEL_SIZE);
+    }
+
+    #[test]
+    fn icon_exposes_pixel_data() {
+        let rgba = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let icon = Icon::from_rgba(rgba.clone(), 1, 2).unwrap();
+        assert_eq!((icon.width(), icon.height()), (1, 2));
+        assert_eq!(icon.rgba(), rgba.as_slice());
+        assert_eq!(icon, Icon::from_rgba(rgba.clone(), 1, 2).unwrap());
+        assert_ne!(icon, Icon::from_rgba(rgba.clone(), 2, 1).unwrap());
+        assert_eq!(icon.into_rgba(), rgba);
+    }
+
+    #[test]
+    fn badge_is_drawn_in_corner() {
+        let icon = RgbaIcon::from_rgba(vec![0; 4 * 4 * PIXEL_SIZE], 4, 4).unwrap();
+        let badge = RgbaIcon::from_rgba(vec![255; PIXEL_SIZE], 1, 1).unwrap();
+
+        let badged = icon.with_badge(&badge, IconCorner::BottomRight);
+        let pixel = |x: usize, y: usize| &badged.rgba[(y * 4 + x) * PIXEL_SIZE..][..PIXEL_SIZE];
+        assert_eq!(pixel(3, 3), [255, 255, 255, 255]);
+        assert_eq!(pixel(2, 2), [255, 255, 255, 255]);
+        assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(1, 3), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn progress_ring_fills_clockwise() {
+        let alpha = |icon: &RgbaIcon, x: u32, y: u32| {
+            icon.rgba[(y * icon.width + x) as usize * PIXEL_SIZE + 3]
+        };
+
+        // top right, bottom right, bottom left and top left of a 32x32 ring
+        let points = [(24, 4), (27, 24), (4, 24), (4, 7)];
+        for (i, progress) in [0.0, 0.3, 0.55, 0.8, 1.0].into_iter().enumerate() {
+            let ring = RgbaIcon::progress_ring(progress, 32, [10, 20, 30]);
+            assert_eq!((ring.width, ring.height), (32, 32));
+            assert_eq!(&ring.rgba[..3], &[10, 20, 30]);
+            // the center and the corners are empty
+            assert_eq!(alpha(&ring, 16, 16), 0);
+            assert_eq!(alpha(&ring, 0, 0), 0);
+
+            for (j, &(x, y)) in points.iter().enumerate() {
+                let expected = if j < i { 255 } else { 64 };
+                assert_eq!(alpha(&ring, x, y), expected, "{progress} at {x}x{y}");
+            }
+        }
+
+        assert_eq!(
+            RgbaIcon::progress_ring(f32::NAN, 8, [0; 3]),
+            RgbaIcon::progress_ring(-1.0, 8, [0; 3])
+        );
+    }
+
+    // a small xorshift generator, so the property tests below are deterministic
+    fn dimensions(seed: u32, count: usize) -> impl Iterator<Item = (u32, u32)> {
+        let mut state = seed;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        (0..count).map(move |_| (next(), next()))
+    }
+
+    #[test]
+    fn zero_sized_icons_are_rejected() {
+        let edges = [0, 1, 16, i32::MAX as u32, u32::MAX];
+        for &other in &edges {
+            for (width, height) in [(0, other), (other, 0)] {
+                assert!(matches!(
+                    RgbaIcon::from_rgba(vec![], width, height),
+                    Err(BadIcon::ZeroSize { .. })
+                ));
+                assert!(matches!(
+                    RgbaIcon::from_rgba(vec![0; PIXEL_SIZE], width, height),
+                    Err(BadIcon::ZeroSize { .. })
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn huge_icons_are_rejected_without_overflowing() {
+        let row_limit = i32::MAX as u32 / PIXEL_SIZE as u32;
+        let edges = [
+            (u32::MAX, u32::MAX),
+            (u32::MAX, 1),
+            (1, u32::MAX),
+            (i32::MAX as u32 + 1, 1),
+            (1, i32::MAX as u32 + 1),
+            (row_limit + 1, 1),
+        ];
+        for (width, height) in edges {
+            assert!(matches!(
+                RgbaIcon::from_rgba(vec![0; PIXEL_SIZE], width, height),
+                Err(BadIcon::TooLarge { .. })
+            ));
+        }
+
+        // the largest row that fits in an `i32` is still accepted as dimensions
+        assert!(matches!(
+            RgbaIcon::from_rgba(vec![0; PIXEL_SIZE], row_limit, 1),
+            Err(BadIcon::DimensionsVsPixelCount { .. })
+        ));
+    }
+
+    #[test]
+    fn arbitrary_dimensions_never_panic() {
+        for (width, height) in dimensions(0x2545_f491, 10_000) {
+            let result = RgbaIcon::from_rgba(vec![0; PIXEL_SIZE], width, height);
+            match result {
+                Ok(icon) => assert_eq!((icon.width, icon.height), (1, 1)),
+                Err(BadIcon::ZeroSize { .. }) => assert!(width == 0 || height == 0),
+                Err(BadIcon::TooLarge { .. }) => {
+                    assert!(width > i32::MAX as u32 / PIXEL_SIZE as u32 || height > i32::MAX as u32)
+                }
+                Err(BadIcon::DimensionsVsPixelCount { width_x_height, .. }) => {
+                    assert_eq!(width_x_height, width as usize * height as usize)
+                }
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn small_dimensions_round_trip() {
+        for (width, height) in dimensions(0x1234_5678, 500) {
+            let (width, height) = (width % 64 + 1, height % 64 + 1);
+            let len = (width * height) as usize * PIXEL_SIZE;
+
+            let icon = RgbaIcon::from_rgba(vec![0; len], width, height).unwrap();
+            assert_eq!((icon.width, icon.height), (width, height));
+
+            assert!(matches!(
+                RgbaIcon::from_rgba(vec![0; len - PIXEL_SIZE], width, height),
+                Err(BadIcon::DimensionsVsPixelCount { .. })
+            ));
+            assert!(matches!(
+                RgbaIcon::from_rgba(vec![0; len + 1], width, height),
+                Err(BadIcon::ByteCountNotDivisibleBy4 { .. })
+            ));
+        }
+    }
+}
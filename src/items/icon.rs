@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.inner
 // SPDX-License-Identifier: MIT
 
-use std::{cell::RefCell, mem, rc::Rc};
+use std::{any::Any, cell::RefCell, mem, rc::Rc, time::Duration};
 
 use crate::{
     accelerator::Accelerator,
@@ -167,6 +167,24 @@ impl IconMenuItem {
         self.inner.borrow_mut().set_enabled(enabled)
     }
 
+    /// Get the tooltip for this icon menu item.
+    pub fn tooltip(&self) -> Option<String> {
+        self.inner.borrow().tooltip()
+    }
+
+    /// Set or remove the tooltip for this icon menu item, shown when hovering it
+    /// and sent with the [`MenuHighlightEvent`](crate::MenuHighlightEvent)s of this icon menu item.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (GTK4 menus)**: Unsupported, the tooltip is only stored.
+    /// - **Windows**: The tooltip isn't shown, it is only sent with the highlight events.
+    pub fn set_tooltip<S: AsRef<str>>(&self, tooltip: Option<S>) {
+        self.inner
+            .borrow_mut()
+            .set_tooltip(tooltip.as_ref().map(|t| t.as_ref()))
+    }
+
     /// Set this icon menu item accelerator.
     pub fn set_accelerator(&self, accelerator: Option<Accelerator>) -> crate::Result<()> {
         self.inner.borrow_mut().set_accelerator(accelerator)
@@ -177,6 +195,38 @@ impl IconMenuItem {
         self.inner.borrow_mut().set_icon(icon)
     }
 
+    /// Animate this menu item icon by cycling through `frames`, showing each one for `interval`.
+    ///
+    /// The animation is replaced by the next call to [`IconMenuItem::set_icon`],
+    /// [`IconMenuItem::set_progress`] or this method. Passing a single frame is
+    /// the same as calling [`IconMenuItem::set_icon`] with it.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux**: The animation only runs while the item is displayed.
+    ///   GTK4 menus only show the first frame.
+    /// - **Windows / macOS**: Unsupported, the first frame is shown instead.
+    pub fn set_animated_icon(&self, frames: Vec<Icon>, interval: Duration) {
+        #[cfg(target_os = "linux")]
+        self.inner.borrow_mut().set_animated_icon(frames, interval);
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = interval;
+            self.inner.borrow_mut().set_icon(frames.into_iter().next())
+        }
+    }
+
+    /// Change this menu item icon to a progress ring, filled clockwise up to `progress`
+    /// which is clamped to `0.0..=1.0`.
+    ///
+    /// Use [`Icon::progress_ring`] and [`IconMenuItem::set_icon`] for a different size or color.
+    pub fn set_progress(&self, progress: f32) {
+        if let Ok(icon) = Icon::progress_ring(progress, 32, [128, 128, 128]) {
+            self.set_icon(Some(icon))
+        }
+    }
+
     /// Change this menu item icon to a native image or remove it.
     ///
     /// ## Platform-specific:
@@ -187,6 +237,39 @@ impl IconMenuItem {
         self.inner.borrow_mut().set_native_icon(_icon)
     }
 
+    /// Attach `data` to this icon menu item, replacing the data attached before.
+    ///
+    /// The data is shared by every clone of this icon menu item,
+    /// including the ones returned by [`Menu::items`](crate::Menu::items).
+    pub fn set_data<T: Any>(&self, data: T) {
+        self.inner.borrow_mut().set_data(Some(Rc::new(data)))
+    }
+
+    /// Get the data attached to this icon menu item with [`IconMenuItem::set_data`],
+    /// or `None` if there is none or it isn't a `T`.
+    pub fn data<T: Any>(&self) -> Option<Rc<T>> {
+        self.inner.borrow().data()?.downcast().ok()
+    }
+
+    /// Remove the data attached to this icon menu item.
+    pub fn remove_data(&self) {
+        self.inner.borrow_mut().set_data(None)
+    }
+
+    /// Returns the ids of the menus and submenus this icon menu item was added to.
+    ///
+    /// The same icon menu item can be added to several menus and submenus. Its state,
+    /// like its text or whether it's enabled, is shared, and changing it
+    /// updates it everywhere.
+    pub fn parents(&self) -> Vec<MenuId> {
+        crate::menu_parent::parent_ids(&self.inner.borrow())
+    }
+
+    /// Remove this icon menu item from all the menus and submenus it was added to.
+    pub fn remove_from_all(&self) {
+        crate::menu_parent::remove_from_all(self)
+    }
+
     /// Convert this menu item into its menu ID.
     pub fn into_id(mut self) -> MenuId {
         // Note: `Rc::into_inner` is available from Rust 1.70
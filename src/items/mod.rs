@@ -59,4 +59,106 @@ mod test {
         let item = PredefinedMenuItem::separator();
         assert_eq!(item.id().clone(), item.into_id());
     }
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn data_is_shared_with_returned_items() {
+        #[derive(Debug, PartialEq)]
+        struct Command(&'static str);
+
+        let item = MenuItem::with_id("open", "Open", true, None);
+        let submenu = Submenu::with_items("File", true, &[&item]).unwrap();
+        let menu = crate::Menu::with_items(&[&submenu]).unwrap();
+        assert!(item.data::<Command>().is_none());
+
+        item.set_data(Command("open"));
+        let found = menu.find_by_id(item.id()).unwrap();
+        assert_eq!(found.data::<Command>().as_deref(), Some(&Command("open")));
+        assert!(found.data::<String>().is_none());
+        assert_eq!(
+            submenu.items()[0].data::<Command>().as_deref(),
+            Some(&Command("open"))
+        );
+
+        found.remove_data();
+        assert!(item.data::<Command>().is_none());
+    }
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn items_know_their_parents() {
+        let item = MenuItem::with_id("open", "Open", true, None);
+        let file = Submenu::with_id_and_items("file", "File", true, &[&item]).unwrap();
+        let menu = crate::Menu::with_id_and_items("menu", &[&file, &item]).unwrap();
+        file.append(&item).unwrap();
+        assert_eq!(item.parents(), [MenuId::new("file"), MenuId::new("menu")]);
+        assert_eq!(file.parents(), [MenuId::new("menu")]);
+
+        // removing one of the two instances in `file` keeps it a parent
+        file.remove(&item).unwrap();
+        assert_eq!(item.parents(), [MenuId::new("menu"), MenuId::new("file")]);
+
+        item.set_text("Open File");
+        assert!(menu
+            .find_by_id(item.id())
+            .is_some_and(|i| i.as_menuitem().unwrap().text() == "Open File"));
+
+        menu.find_by_id(item.id()).unwrap().remove_from_all();
+        assert!(item.parents().is_empty());
+        assert!(file.items().is_empty());
+        assert_eq!(menu.items().len(), 1);
+
+        // dropped parents aren't returned
+        let edit = Submenu::with_items("Edit", true, &[&item]).unwrap();
+        assert_eq!(item.parents().len(), 1);
+        drop(edit);
+        assert!(item.parents().is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn deep_clones_share_no_state() {
+        let wrap = CheckMenuItem::with_id("wrap", "Wrap", true, true, None);
+        let open = MenuItem::with_id("open", "Open", false, None);
+        let recent = Submenu::with_id_and_items("recent", "Recent", true, &[&open]).unwrap();
+        let file = Submenu::with_id_and_items(
+            "file",
+            "File",
+            true,
+            &[&wrap, &PredefinedMenuItem::separator(), &recent],
+        )
+        .unwrap();
+        file.set_tooltip(Some("File actions"));
+
+        let copy = file
+            .deep_clone(|id| (id != "recent").then(|| MenuId::new(format!("copy.{}", id.as_ref()))))
+            .unwrap();
+        assert_eq!(copy.id(), "copy.file");
+        assert_eq!(copy.text(), "File");
+        assert_eq!(copy.tooltip().as_deref(), Some("File actions"));
+
+        let items = copy.items();
+        assert_eq!(items.len(), 3);
+        let wrap_copy = items[0].as_check_menuitem().unwrap();
+        assert_eq!(wrap_copy.id(), "copy.wrap");
+        assert!(wrap_copy.is_checked());
+        assert!(items[1].as_predefined_menuitem().is_some());
+        let recent_copy = items[2].as_submenu().unwrap();
+        assert_ne!(recent_copy.id(), "recent");
+        let open_copy = recent_copy.items()[0].as_menuitem().unwrap().clone();
+        assert_eq!(open_copy.id(), "copy.open");
+        assert!(!open_copy.is_enabled());
+
+        wrap_copy.set_checked(false);
+        open_copy.set_text("Open Recent");
+        assert!(wrap.is_checked());
+        assert_eq!(open.text(), "Open");
+        assert_eq!(open.parents(), [MenuId::new("recent")]);
+
+        let menu = crate::Menu::with_id_and_items("menu", &[&file]).unwrap();
+        let menu_copy = menu.deep_clone(|_| None).unwrap();
+        assert_ne!(menu_copy.id(), "menu");
+        assert_eq!(menu_copy.items()[0].as_submenu().unwrap().text(), "File");
+        assert_ne!(menu_copy.items()[0].id(), "file");
+    }
 }
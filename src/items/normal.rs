@@ -1,4 +1,4 @@
-use std::{cell::RefCell, mem, rc::Rc};
+use std::{any::Any, cell::RefCell, mem, rc::Rc};
 
 use crate::{accelerator::Accelerator, sealed::IsMenuItemBase, IsMenuItem, MenuId, MenuItemKind};
 
@@ -89,11 +89,62 @@ impl MenuItem {
         self.inner.borrow_mut().set_enabled(enabled)
     }
 
+    /// Get the tooltip for this menu item.
+    pub fn tooltip(&self) -> Option<String> {
+        self.inner.borrow().tooltip()
+    }
+
+    /// Set or remove the tooltip for this menu item, shown when hovering it
+    /// and sent with the [`MenuHighlightEvent`](crate::MenuHighlightEvent)s of this menu item.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (GTK4 menus)**: Unsupported, the tooltip is only stored.
+    /// - **Windows**: The tooltip isn't shown, it is only sent with the highlight events.
+    pub fn set_tooltip<S: AsRef<str>>(&self, tooltip: Option<S>) {
+        self.inner
+            .borrow_mut()
+            .set_tooltip(tooltip.as_ref().map(|t| t.as_ref()))
+    }
+
     /// Set this menu item accelerator.
     pub fn set_accelerator(&self, accelerator: Option<Accelerator>) -> crate::Result<()> {
         self.inner.borrow_mut().set_accelerator(accelerator)
     }
 
+    /// Attach `data` to this menu item, replacing the data attached before.
+    ///
+    /// The data is shared by every clone of this menu item,
+    /// including the ones returned by [`Menu::items`](crate::Menu::items).
+    pub fn set_data<T: Any>(&self, data: T) {
+        self.inner.borrow_mut().set_data(Some(Rc::new(data)))
+    }
+
+    /// Get the data attached to this menu item with [`MenuItem::set_data`],
+    /// or `None` if there is none or it isn't a `T`.
+    pub fn data<T: Any>(&self) -> Option<Rc<T>> {
+        self.inner.borrow().data()?.downcast().ok()
+    }
+
+    /// Remove the data attached to this menu item.
+    pub fn remove_data(&self) {
+        self.inner.borrow_mut().set_data(None)
+    }
+
+    /// Returns the ids of the menus and submenus this menu item was added to.
+    ///
+    /// The same menu item can be added to several menus and submenus. Its state,
+    /// like its text or whether it's enabled, is shared, and changing it
+    /// updates it everywhere.
+    pub fn parents(&self) -> Vec<MenuId> {
+        crate::menu_parent::parent_ids(&self.inner.borrow())
+    }
+
+    /// Remove this menu item from all the menus and submenus it was added to.
+    pub fn remove_from_all(&self) {
+        crate::menu_parent::remove_from_all(self)
+    }
+
     /// Convert this menu item into its menu ID.
     pub fn into_id(mut self) -> MenuId {
         // Note: `Rc::into_inner` is available from Rust 1.70
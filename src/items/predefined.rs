@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.inner
 // SPDX-License-Identifier: MIT
 
-use std::{cell::RefCell, mem, rc::Rc};
+use std::{any::Any, cell::RefCell, mem, rc::Rc};
 
 use crate::{
     accelerator::{Accelerator, CMD_OR_CTRL},
@@ -63,7 +63,9 @@ impl PredefinedMenuItem {
     ///
     /// ## Platform-specific:
     ///
-    /// - **Windows / Linux:** Unsupported.
+    /// - **Windows:** Unsupported.
+    /// - **Linux:** Unsupported without the `libxdo` feature,
+    ///   and only works on X11 as the keys are sent through `libxdo`.
     pub fn undo(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Undo, text)
     }
@@ -71,16 +73,14 @@ impl PredefinedMenuItem {
     ///
     /// ## Platform-specific:
     ///
-    /// - **Windows / Linux:** Unsupported.
+    /// - **Windows:** Unsupported.
+    /// - **Linux:** Unsupported without the `libxdo` feature,
+    ///   and only works on X11 as the keys are sent through `libxdo`.
     pub fn redo(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Redo, text)
     }
 
     /// Minimize window menu item
-    ///
-    /// ## Platform-specific:
-    ///
-    /// - **Linux:** Unsupported.
     pub fn minimize(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Minimize, text)
     }
@@ -89,7 +89,7 @@ impl PredefinedMenuItem {
     ///
     /// ## Platform-specific:
     ///
-    /// - **Linux:** Unsupported.
+    /// - **Linux:** Toggles between the maximized and unmaximized states.
     pub fn maximize(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Maximize, text)
     }
@@ -98,16 +98,12 @@ impl PredefinedMenuItem {
     ///
     /// ## Platform-specific:
     ///
-    /// - **Windows / Linux:** Unsupported.
+    /// - **Windows:** Unsupported.
     pub fn fullscreen(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Fullscreen, text)
     }
 
     /// Hide window menu item
-    ///
-    /// ## Platform-specific:
-    ///
-    /// - **Linux:** Unsupported.
     pub fn hide(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Hide, text)
     }
@@ -131,10 +127,6 @@ impl PredefinedMenuItem {
     }
 
     /// Close window menu item
-    ///
-    /// ## Platform-specific:
-    ///
-    /// - **Linux:** Unsupported.
     pub fn close_window(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::CloseWindow, text)
     }
@@ -143,12 +135,19 @@ impl PredefinedMenuItem {
     ///
     /// ## Platform-specific:
     ///
-    /// - **Linux:** Unsupported.
+    /// - **Linux:** Emits a [`MenuEvent`](crate::MenuEvent) with the id of this item
+    ///   and quits the gtk main loop if it is running.
     pub fn quit(text: Option<&str>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::Quit, text)
     }
 
     /// About app menu item
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux:** Shows a non-modal dialog, transient for the window owning the menu,
+    ///   which can be replaced using `AboutMetadata::set_gtk_dialog_handler`,
+    ///   or `AboutMetadata::set_gtk4_dialog_handler` for GTK4 menus.
     pub fn about(text: Option<&str>, metadata: Option<AboutMetadata>) -> PredefinedMenuItem {
         PredefinedMenuItem::new(PredefinedMenuItemType::About(metadata), text)
     }
@@ -197,6 +196,39 @@ impl PredefinedMenuItem {
         self.inner.borrow_mut().set_text(text.as_ref())
     }
 
+    /// Attach `data` to this predefined menu item, replacing the data attached before.
+    ///
+    /// The data is shared by every clone of this predefined menu item,
+    /// including the ones returned by [`Menu::items`](crate::Menu::items).
+    pub fn set_data<T: Any>(&self, data: T) {
+        self.inner.borrow_mut().set_data(Some(Rc::new(data)))
+    }
+
+    /// Get the data attached to this predefined menu item with [`PredefinedMenuItem::set_data`],
+    /// or `None` if there is none or it isn't a `T`.
+    pub fn data<T: Any>(&self) -> Option<Rc<T>> {
+        self.inner.borrow().data()?.downcast().ok()
+    }
+
+    /// Remove the data attached to this predefined menu item.
+    pub fn remove_data(&self) {
+        self.inner.borrow_mut().set_data(None)
+    }
+
+    /// Returns the ids of the menus and submenus this menu item was added to.
+    ///
+    /// The same menu item can be added to several menus and submenus. Its state,
+    /// like its text or whether it's enabled, is shared, and changing it
+    /// updates it everywhere.
+    pub fn parents(&self) -> Vec<MenuId> {
+        crate::menu_parent::parent_ids(&self.inner.borrow())
+    }
+
+    /// Remove this menu item from all the menus and submenus it was added to.
+    pub fn remove_from_all(&self) {
+        crate::menu_parent::remove_from_all(self)
+    }
+
     /// Convert this menu item into its menu ID.
     pub fn into_id(mut self) -> MenuId {
         // Note: `Rc::into_inner` is available from Rust 1.70
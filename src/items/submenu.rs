@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.inner
 // SPDX-License-Identifier: MIT
 
-use std::{cell::RefCell, mem, rc::Rc};
+use std::{any::Any, cell::RefCell, mem, rc::Rc};
 
+#[cfg(target_os = "linux")]
+use crate::ContextMenuOptions;
 use crate::{
-    dpi::Position, sealed::IsMenuItemBase, util::AddOp, ContextMenu, IsMenuItem, MenuId,
-    MenuItemKind,
+    dpi::Position, menu_index, menu_parent, sealed::IsMenuItemBase, util::AddOp, ContextMenu,
+    IsMenuItem, MenuId, MenuItemKind, MenuParent,
 };
 
 /// A menu that can be added to a [`Menu`] or another [`Submenu`].
@@ -93,7 +95,7 @@ impl Submenu {
 
     /// Add a menu item to the end of this menu.
     pub fn append(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
-        self.inner.borrow_mut().add_menu_item(item, AddOp::Append)
+        self.add_menu_item(item, AddOp::Append)
     }
 
     /// Add menu items to the end of this submenu. It calls [`Submenu::append`] in a loop.
@@ -107,9 +109,7 @@ impl Submenu {
 
     /// Add a menu item to the beginning of this submenu.
     pub fn prepend(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
-        self.inner
-            .borrow_mut()
-            .add_menu_item(item, AddOp::Insert(0))
+        self.add_menu_item(item, AddOp::Insert(0))
     }
 
     /// Add menu items to the beginning of this submenu.
@@ -121,9 +121,19 @@ impl Submenu {
 
     /// Insert a menu item at the specified `postion` in the submenu.
     pub fn insert(&self, item: &dyn IsMenuItem, position: usize) -> crate::Result<()> {
-        self.inner
-            .borrow_mut()
-            .add_menu_item(item, AddOp::Insert(position))
+        self.add_menu_item(item, AddOp::Insert(position))
+    }
+
+    fn add_menu_item(&self, item: &dyn IsMenuItem, op: AddOp) -> crate::Result<()> {
+        let menus = menu_parent::root_menus(&self.inner.borrow());
+        for menu in menus {
+            menu.check_unique_ids(item)?;
+        }
+
+        self.inner.borrow_mut().add_menu_item(item, op)?;
+        menu_parent::added_to(item, MenuParent::Submenu(self));
+        menu_index::layout_changed(MenuParent::Submenu(self));
+        Ok(())
     }
 
     /// Insert menu items at the specified `postion` in the submenu.
@@ -137,7 +147,10 @@ impl Submenu {
 
     /// Remove a menu item from this submenu.
     pub fn remove(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
-        self.inner.borrow_mut().remove(item)
+        self.inner.borrow_mut().remove(item)?;
+        menu_parent::removed_from(item, MenuParent::Submenu(self));
+        menu_index::layout_changed(MenuParent::Submenu(self));
+        Ok(())
     }
 
     /// Remove the menu item at the specified position from this submenu and returns it.
@@ -152,11 +165,119 @@ impl Submenu {
         }
     }
 
+    /// Move a menu item of this submenu to `position`, which is clamped to the last position.
+    ///
+    /// The native items are moved without being recreated.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS:** The item is removed and inserted again,
+    ///   and put back where it was if inserting it fails.
+    pub fn move_item(&self, item: &dyn IsMenuItem, position: usize) -> crate::Result<()> {
+        #[cfg(target_os = "linux")]
+        let res = self.inner.borrow_mut().move_item(item, position);
+        #[cfg(not(target_os = "linux"))]
+        let res = {
+            let mut inner = self.inner.borrow_mut();
+            let index = inner.items().iter().position(|i| i.id() == item.id());
+            inner.remove(item).and_then(|_| {
+                let position = position.min(inner.items().len());
+                let res = inner.add_menu_item(item, AddOp::Insert(position));
+                // put the item back where it was, like `move_item_to` does
+                if let (Err(_), Some(index)) = (&res, index) {
+                    let _ = inner.add_menu_item(item, AddOp::Insert(index));
+                }
+                res
+            })
+        };
+        menu_index::layout_changed(MenuParent::Submenu(self));
+        res
+    }
+
+    /// Move a menu item of this submenu to `position` in `parent`, another menu or submenu.
+    ///
+    /// The item is removed from this submenu and inserted into `parent`, `position` is clamped
+    /// to the end of `parent`, and if `parent` is this submenu, this is the same as [`Submenu::move_item`].
+    /// Moving a submenu into itself or one of its own submenus returns [`Error::SubmenuIntoItself`].
+    ///
+    /// [`Error::SubmenuIntoItself`]: crate::Error::SubmenuIntoItself
+    pub fn move_item_to<'a>(
+        &self,
+        item: &dyn IsMenuItem,
+        parent: impl Into<MenuParent<'a>>,
+        position: usize,
+    ) -> crate::Result<()> {
+        MenuParent::from(self).move_item_to(item, parent.into(), position)
+    }
+
+    /// Remove all the menu items of this submenu.
+    pub fn clear(&self) {
+        for item in self.items() {
+            let _ = self.remove(item.as_ref());
+        }
+    }
+
+    /// Replace all the menu items of this submenu with `items`.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux**: The GTK items of `items` are created at once after they were all added.
+    pub fn replace_items(&self, items: &[&dyn IsMenuItem]) -> crate::Result<()> {
+        self.clear();
+
+        #[cfg(target_os = "linux")]
+        if self.begin_batch() {
+            let res = self.append_items(items);
+            return res.and(self.end_batch());
+        }
+
+        self.append_items(items)
+    }
+
+    /// Defers creating the GTK items of the items added to this submenu
+    /// until [`Submenu::end_batch`], returns `false` if it was already deferred.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn begin_batch(&self) -> bool {
+        self.inner.borrow_mut().begin_batch()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn end_batch(&self) -> crate::Result<()> {
+        self.inner.borrow_mut().end_batch()
+    }
+
     /// Returns a list of menu items that has been added to this submenu.
     pub fn items(&self) -> Vec<MenuItemKind> {
         self.inner.borrow().items()
     }
 
+    /// Creates a copy of this submenu and its items, recursively, that shares no state
+    /// with them, for example to show the same context menu for several documents.
+    ///
+    /// The copies have the same text, enabled and checked state, icon, accelerator and tooltip.
+    /// Native icons are copied too, and animated icons start over from their first frame.
+    /// `id_mapper` is called with the id of each item and returns the id of its copy,
+    /// or `None` to give it a new id. Data set with [`Submenu::set_data`] isn't copied,
+    /// and an item added more than once is copied each time.
+    ///
+    /// ```no_run
+    /// # use muda::{MenuId, MenuItem, Submenu};
+    /// let open = MenuItem::with_id("open", "Open", true, None);
+    /// let file = Submenu::with_items("File", true, &[&open]).unwrap();
+    ///
+    /// let copy = file
+    ///     .deep_clone(|id| Some(MenuId::new(format!("doc-1.{}", id.as_ref()))))
+    ///     .unwrap();
+    /// assert_eq!(copy.items()[0].id(), "doc-1.open");
+    /// ```
+    pub fn deep_clone<F: FnMut(&MenuId) -> Option<MenuId>>(
+        &self,
+        mut id_mapper: F,
+    ) -> crate::Result<Submenu> {
+        let copy = MenuItemKind::Submenu(self.clone()).deep_clone(&mut id_mapper)?;
+        Ok(copy.as_submenu_unchecked().clone())
+    }
+
     /// Get the text for this submenu.
     pub fn text(&self) -> String {
         self.inner.borrow().text()
@@ -179,6 +300,25 @@ impl Submenu {
         self.inner.borrow_mut().set_enabled(enabled)
     }
 
+    /// Get the tooltip for this submenu.
+    pub fn tooltip(&self) -> Option<String> {
+        self.inner.borrow().tooltip()
+    }
+
+    /// Set or remove the tooltip for this submenu, shown when hovering it
+    /// and sent with the [`MenuHighlightEvent`](crate::MenuHighlightEvent)s of this submenu.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (GTK4 menus)**: Unsupported, the tooltip is only stored.
+    /// - **Windows**: The tooltip isn't shown, it is only sent with the highlight events.
+    /// - **macOS**: No highlight events are sent for submenus.
+    pub fn set_tooltip<S: AsRef<str>>(&self, tooltip: Option<S>) {
+        self.inner
+            .borrow_mut()
+            .set_tooltip(tooltip.as_ref().map(|t| t.as_ref()))
+    }
+
     /// Set this submenu as the Window menu for the application on macOS.
     ///
     /// This will cause macOS to automatically add window-switching items and
@@ -199,6 +339,70 @@ impl Submenu {
         self.inner.borrow_mut().set_as_help_menu_for_nsapp()
     }
 
+    /// Publishes this submenu on the session bus at `object_path` using the
+    /// `com.canonical.dbusmenu` protocol, see [`Menu::serve_dbusmenu`](crate::Menu::serve_dbusmenu).
+    ///
+    /// Requires the `dbusmenu` feature.
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    pub fn serve_dbusmenu(&self, object_path: &str) -> crate::Result<crate::DBusMenuServer> {
+        self.serve_dbusmenu_inner(None, object_path)
+    }
+
+    /// Publishes this submenu at `object_path` on `connection`, see [`Menu::serve_dbusmenu`](crate::Menu::serve_dbusmenu).
+    ///
+    /// Requires the `dbusmenu` feature.
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    pub fn serve_dbusmenu_on_connection(
+        &self,
+        connection: &gtk::gio::DBusConnection,
+        object_path: &str,
+    ) -> crate::Result<crate::DBusMenuServer> {
+        self.serve_dbusmenu_inner(Some(connection), object_path)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    fn serve_dbusmenu_inner(
+        &self,
+        connection: Option<&gtk::gio::DBusConnection>,
+        object_path: &str,
+    ) -> crate::Result<crate::DBusMenuServer> {
+        let root = crate::platform_impl::DBusMenuRoot::Submenu(Rc::downgrade(&self.inner));
+        crate::DBusMenuServer::serve(root, connection, object_path)
+    }
+
+    /// Attach `data` to this submenu, replacing the data attached before.
+    ///
+    /// The data is shared by every clone of this submenu,
+    /// including the ones returned by [`Menu::items`](crate::Menu::items).
+    pub fn set_data<T: Any>(&self, data: T) {
+        self.inner.borrow_mut().set_data(Some(Rc::new(data)))
+    }
+
+    /// Get the data attached to this submenu with [`Submenu::set_data`],
+    /// or `None` if there is none or it isn't a `T`.
+    pub fn data<T: Any>(&self) -> Option<Rc<T>> {
+        self.inner.borrow().data()?.downcast().ok()
+    }
+
+    /// Remove the data attached to this submenu.
+    pub fn remove_data(&self) {
+        self.inner.borrow_mut().set_data(None)
+    }
+
+    /// Returns the ids of the menus and submenus this submenu was added to.
+    ///
+    /// The same submenu can be added to several menus and submenus. Its state,
+    /// like its text or whether it's enabled, is shared, and changing it
+    /// updates it everywhere.
+    pub fn parents(&self) -> Vec<MenuId> {
+        crate::menu_parent::parent_ids(&self.inner.borrow())
+    }
+
+    /// Remove this submenu from all the menus and submenus it was added to.
+    pub fn remove_from_all(&self) {
+        crate::menu_parent::remove_from_all(self)
+    }
+
     /// Convert this submenu into its menu ID.
     pub fn into_id(mut self) -> MenuId {
         // Note: `Rc::into_inner` is available from Rust 1.70
@@ -239,9 +443,92 @@ impl ContextMenu for Submenu {
         w: &gtk::Window,
         position: Option<Position>,
     ) -> bool {
-        self.inner
-            .borrow_mut()
-            .show_context_menu_for_gtk_window(w, position)
+        self.show_context_menu_for_gtk_window_with_options(w, ContextMenuOptions::at(position))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_callback(
+        &self,
+        w: &gtk::Window,
+        position: Option<Position>,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        self.show_context_menu_for_gtk_window_with_options_and_callback(
+            w,
+            ContextMenuOptions::at(position),
+            callback,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_options(
+        &self,
+        w: &gtk::Window,
+        options: ContextMenuOptions,
+    ) -> bool {
+        crate::platform_impl::show_context_menu(self.gtk_context_menu(), w, &options)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_options_and_callback(
+        &self,
+        w: &gtk::Window,
+        options: ContextMenuOptions,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        crate::platform_impl::show_context_menu_with_callback(
+            self.gtk_context_menu(),
+            w,
+            &options,
+            callback,
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window(
+        &self,
+        w: &gtk4::Window,
+        position: Option<Position>,
+    ) -> bool {
+        self.show_context_menu_for_gtk4_window_with_options(w, ContextMenuOptions::at(position))
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_callback(
+        &self,
+        w: &gtk4::Window,
+        position: Option<Position>,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        self.show_context_menu_for_gtk4_window_with_options_and_callback(
+            w,
+            ContextMenuOptions::at(position),
+            callback,
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_options(
+        &self,
+        w: &gtk4::Window,
+        options: ContextMenuOptions,
+    ) -> bool {
+        crate::platform_impl::gtk4::show_context_menu(&self.gtk4_context_menu(), w, &options)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_options_and_callback(
+        &self,
+        w: &gtk4::Window,
+        options: ContextMenuOptions,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        crate::platform_impl::gtk4::show_context_menu_with_callback(
+            &self.gtk4_context_menu(),
+            w,
+            &options,
+            callback,
+        )
     }
 
     #[cfg(target_os = "linux")]
@@ -249,6 +536,11 @@ impl ContextMenu for Submenu {
         self.inner.borrow_mut().gtk_context_menu()
     }
 
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn gtk4_context_menu(&self) -> gtk4::PopoverMenu {
+        crate::platform_impl::gtk4::Gtk4Submenu::context_menu(&self.inner)
+    }
+
     #[cfg(target_os = "macos")]
     unsafe fn show_context_menu_for_nsview(
         &self,
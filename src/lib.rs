@@ -23,7 +23,7 @@
 //!
 //! # Dependencies (Linux Only)
 //!
-//! `gtk` is used for menus and `libxdo` is used to make the predfined `Copy`, `Cut`, `Paste` and `SelectAll` menu items work. Be sure to install following packages before building:
+//! `gtk` is used for menus and `libxdo` is used to make the predfined `Undo` and `Redo` menu items work. The `Copy`, `Cut`, `Paste` and `SelectAll` menu items act on the focused widget directly, and only fall back to `libxdo` when it doesn't support them. Be sure to install following packages before building:
 //!
 //! #### Arch Linux / Manjaro:
 //!
@@ -153,28 +153,49 @@
 //! [winit]: https://docs.rs/winit
 //! [tao]: https://docs.rs/tao
 
+use std::rc::Rc;
+
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 
 pub mod about_metadata;
 pub mod accelerator;
 mod builders;
+mod command_registry;
+#[cfg(target_os = "linux")]
+mod context_menu;
+#[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+mod dbusmenu;
 mod error;
 mod icon;
 mod items;
 mod menu;
+#[cfg(target_os = "linux")]
+mod menu_handle;
 mod menu_id;
+mod menu_index;
+mod menu_parent;
 mod platform_impl;
 mod util;
 
-pub use about_metadata::AboutMetadata;
+pub use about_metadata::{AboutMetadata, LicenseKind};
 pub use builders::*;
+pub use command_registry::{CommandRegistry, CommandState};
+#[cfg(target_os = "linux")]
+pub use context_menu::*;
+#[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+pub use dbusmenu::DBusMenuServer;
 pub use dpi;
 pub use error::*;
-pub use icon::{BadIcon, Icon, NativeIcon};
+#[cfg(target_os = "linux")]
+pub use icon::IconCacheStats;
+pub use icon::{BadIcon, Icon, IconCorner, NativeIcon};
 pub use items::*;
 pub use menu::*;
+#[cfg(target_os = "linux")]
+pub use menu_handle::{MenuHandle, MenuHandleItem};
 pub use menu_id::MenuId;
+pub use menu_parent::MenuParent;
 
 /// An enumeration of all available menu types, useful to match against
 /// the items returned from [`Menu::items`] or [`Submenu::items`]
@@ -279,6 +300,35 @@ impl MenuItemKind {
         }
     }
 
+    /// Attach `data` to this item, see [`MenuItem::set_data`].
+    pub fn set_data<T: std::any::Any>(&self, data: T) {
+        self.child_mut().set_data(Some(Rc::new(data)))
+    }
+
+    /// Get the data attached to this item, see [`MenuItem::data`].
+    pub fn data<T: std::any::Any>(&self) -> Option<Rc<T>> {
+        self.child().data()?.downcast().ok()
+    }
+
+    /// Remove the data attached to this item.
+    pub fn remove_data(&self) {
+        self.child_mut().set_data(None)
+    }
+
+    /// Returns the ids of the menus and submenus this item was added to.
+    ///
+    /// The same item can be added to several menus and submenus. Its state,
+    /// like its text or whether it's enabled, is shared, and changing it
+    /// updates it everywhere.
+    pub fn parents(&self) -> Vec<MenuId> {
+        crate::menu_parent::parent_ids(&self.child())
+    }
+
+    /// Remove this item from all the menus and submenus it was added to.
+    pub fn remove_from_all(&self) {
+        crate::menu_parent::remove_from_all(self.as_ref())
+    }
+
     /// Convert this item into its menu ID.
     pub fn into_id(self) -> MenuId {
         match self {
@@ -382,12 +432,140 @@ pub trait ContextMenu {
         position: Option<dpi::Position>,
     ) -> bool;
 
+    /// Shows this menu as a context menu inside a [`gtk4::Window`]
+    ///
+    /// - `position` is relative to the window top-left corner, if `None`, the cursor position is used.
+    ///
+    /// Returns `true` once the menu is closed, and `false` if it couldn't be shown.
+    ///
+    /// The default implementation doesn't show anything and returns `false`.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window(
+        &self,
+        w: &gtk4::Window,
+        position: Option<dpi::Position>,
+    ) -> bool {
+        let _ = (w, position);
+        false
+    }
+
+    /// Shows this menu as a context menu inside a [`gtk::Window`] without blocking.
+    ///
+    /// Unlike [`ContextMenu::show_context_menu_for_gtk_window`], this returns right away
+    /// instead of iterating the GTK main loop until the menu is closed, so it is safe
+    /// to call from inside event handlers.
+    ///
+    /// - `position` is relative to the window top-left corner, if `None`, the cursor position is used.
+    /// - `callback` is called from the main loop once the menu is closed, with the same value
+    ///   [`ContextMenu::show_context_menu_for_gtk_window`] would have returned.
+    ///
+    /// The default implementation blocks in [`ContextMenu::show_context_menu_for_gtk_window`]
+    /// and then calls `callback`.
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_callback(
+        &self,
+        w: &gtk::Window,
+        position: Option<dpi::Position>,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        callback(self.show_context_menu_for_gtk_window(w, position));
+    }
+
+    /// Shows this menu as a context menu inside a [`gtk4::Window`] without blocking.
+    ///
+    /// Unlike [`ContextMenu::show_context_menu_for_gtk4_window`], this returns right away
+    /// instead of iterating the GLib main context until the menu is closed, so it is safe
+    /// to call from inside event handlers.
+    ///
+    /// - `position` is relative to the window top-left corner, if `None`, the cursor position is used.
+    /// - `callback` is called from the main loop once the menu is closed, with the same value
+    ///   [`ContextMenu::show_context_menu_for_gtk4_window`] would have returned.
+    ///
+    /// The default implementation calls [`ContextMenu::show_context_menu_for_gtk4_window`]
+    /// and then `callback`.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_callback(
+        &self,
+        w: &gtk4::Window,
+        position: Option<dpi::Position>,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        callback(self.show_context_menu_for_gtk4_window(w, position));
+    }
+
+    /// Shows this menu as a context menu inside a [`gtk::Window`], anchored as described by `options`.
+    ///
+    /// Returns the same value as [`ContextMenu::show_context_menu_for_gtk_window`].
+    ///
+    /// The default implementation only uses the position of the anchor, see [`ContextMenuOptions::position`].
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_options(
+        &self,
+        w: &gtk::Window,
+        options: ContextMenuOptions,
+    ) -> bool {
+        self.show_context_menu_for_gtk_window(w, options.position())
+    }
+
+    /// Shows this menu as a context menu inside a [`gtk4::Window`], anchored as described by `options`.
+    ///
+    /// Returns the same value as [`ContextMenu::show_context_menu_for_gtk4_window`].
+    ///
+    /// The default implementation only uses the position of the anchor, see [`ContextMenuOptions::position`].
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_options(
+        &self,
+        w: &gtk4::Window,
+        options: ContextMenuOptions,
+    ) -> bool {
+        self.show_context_menu_for_gtk4_window(w, options.position())
+    }
+
+    /// Shows this menu as a context menu inside a [`gtk::Window`], anchored as described by `options`,
+    /// without blocking, see [`ContextMenu::show_context_menu_for_gtk_window_with_callback`].
+    ///
+    /// The default implementation only uses the position of the anchor, see [`ContextMenuOptions::position`].
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_options_and_callback(
+        &self,
+        w: &gtk::Window,
+        options: ContextMenuOptions,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        self.show_context_menu_for_gtk_window_with_callback(w, options.position(), callback);
+    }
+
+    /// Shows this menu as a context menu inside a [`gtk4::Window`], anchored as described by `options`,
+    /// without blocking, see [`ContextMenu::show_context_menu_for_gtk4_window_with_callback`].
+    ///
+    /// The default implementation only uses the position of the anchor, see [`ContextMenuOptions::position`].
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_options_and_callback(
+        &self,
+        w: &gtk4::Window,
+        options: ContextMenuOptions,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        self.show_context_menu_for_gtk4_window_with_callback(w, options.position(), callback);
+    }
+
     /// Get the underlying gtk menu reserved for context menus.
     ///
     /// The returned [`gtk::Menu`] is valid as long as the `ContextMenu` is.
     #[cfg(target_os = "linux")]
     fn gtk_context_menu(&self) -> gtk::Menu;
 
+    /// Get the underlying [`gtk4::PopoverMenu`] reserved for context menus.
+    ///
+    /// The returned popover is valid as long as the `ContextMenu` is,
+    /// its parent is set to the window it was last shown in.
+    ///
+    /// The default implementation returns a new empty popover.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn gtk4_context_menu(&self) -> gtk4::PopoverMenu {
+        gtk4::PopoverMenu::from_model(None::<&gtk4::gio::MenuModel>)
+    }
+
     /// Shows this menu as a context menu for the specified `NSView`.
     ///
     /// - `position` is relative to the window top-left corner, if `None`, the cursor position is used.
@@ -465,3 +643,71 @@ impl MenuEvent {
         }
     }
 }
+
+/// Describes an event emitted when a menu item is highlighted, for example when hovered
+/// with the mouse or selected with the keyboard, useful to show its tooltip in a status bar.
+///
+/// ## Platform-specific:
+///
+/// - **Linux**: Only emitted by the GTK3 menus, not by the GTK4 menus.
+/// - **macOS**: Not emitted for submenus.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MenuHighlightEvent {
+    /// Id of the menu item which was highlighted
+    pub id: MenuId,
+    /// Tooltip of the menu item which was highlighted
+    pub tooltip: Option<String>,
+}
+
+/// A reciever that could be used to listen to menu highlight events.
+pub type MenuHighlightEventReceiver = Receiver<MenuHighlightEvent>;
+type MenuHighlightEventHandler = Box<dyn Fn(MenuHighlightEvent) + Send + Sync + 'static>;
+
+static MENU_HIGHLIGHT_CHANNEL: Lazy<(Sender<MenuHighlightEvent>, MenuHighlightEventReceiver)> =
+    Lazy::new(unbounded);
+static MENU_HIGHLIGHT_EVENT_HANDLER: OnceCell<Option<MenuHighlightEventHandler>> = OnceCell::new();
+
+impl MenuHighlightEvent {
+    /// Returns the id of the menu item which was highlighted
+    pub fn id(&self) -> &MenuId {
+        &self.id
+    }
+
+    /// Returns the tooltip of the menu item which was highlighted
+    pub fn tooltip(&self) -> Option<&str> {
+        self.tooltip.as_deref()
+    }
+
+    /// Gets a reference to the event channel's [`MenuHighlightEventReceiver`]
+    /// which can be used to listen for menu highlight events.
+    ///
+    /// ## Note
+    ///
+    /// This will not receive any events if [`MenuHighlightEvent::set_event_handler`] has been called with a `Some` value.
+    pub fn receiver<'a>() -> &'a MenuHighlightEventReceiver {
+        &MENU_HIGHLIGHT_CHANNEL.1
+    }
+
+    /// Set a handler to be called for new events. Useful for implementing custom event sender.
+    ///
+    /// ## Note
+    ///
+    /// Calling this function with a `Some` value,
+    /// will not send new events to the channel associated with [`MenuHighlightEvent::receiver`]
+    pub fn set_event_handler<F: Fn(MenuHighlightEvent) + Send + Sync + 'static>(f: Option<F>) {
+        if let Some(f) = f {
+            let _ = MENU_HIGHLIGHT_EVENT_HANDLER.set(Some(Box::new(f)));
+        } else {
+            let _ = MENU_HIGHLIGHT_EVENT_HANDLER.set(None);
+        }
+    }
+
+    pub(crate) fn send(event: MenuHighlightEvent) {
+        if let Some(handler) = MENU_HIGHLIGHT_EVENT_HANDLER.get_or_init(|| None) {
+            handler(event);
+        } else {
+            let _ = MENU_HIGHLIGHT_CHANNEL.0.send(event);
+        }
+    }
+}
@@ -4,7 +4,15 @@
 
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{dpi::Position, util::AddOp, ContextMenu, IsMenuItem, MenuId, MenuItemKind};
+#[cfg(target_os = "linux")]
+use crate::ContextMenuOptions;
+use crate::{
+    dpi::Position,
+    menu_index::{self, MenuIndex},
+    menu_parent,
+    util::AddOp,
+    ContextMenu, IsMenuItem, MenuId, MenuItemKind, MenuParent,
+};
 
 /// A root menu that can be added to a Window on Windows and Linux
 /// and used as the app global menu on macOS.
@@ -12,6 +20,64 @@ use crate::{dpi::Position, util::AddOp, ContextMenu, IsMenuItem, MenuId, MenuIte
 pub struct Menu {
     id: Rc<MenuId>,
     inner: Rc<RefCell<crate::platform_impl::Menu>>,
+    index: Rc<RefCell<MenuIndex>>,
+}
+
+/// A weak reference to a [`Menu`].
+#[derive(Clone)]
+pub(crate) struct WeakMenu {
+    id: Rc<MenuId>,
+    inner: std::rc::Weak<RefCell<crate::platform_impl::Menu>>,
+    index: std::rc::Weak<RefCell<MenuIndex>>,
+}
+
+impl WeakMenu {
+    pub(crate) fn id(&self) -> &MenuId {
+        &self.id
+    }
+
+    /// Whether this refers to `menu`.
+    pub(crate) fn ptr_eq(&self, menu: &Menu) -> bool {
+        std::ptr::eq(self.inner.as_ptr(), Rc::as_ptr(&menu.inner))
+    }
+
+    pub(crate) fn upgrade(&self) -> Option<Menu> {
+        Some(Menu {
+            id: self.id.clone(),
+            inner: self.inner.upgrade()?,
+            index: self.index.upgrade()?,
+        })
+    }
+}
+
+/// Ends the batches started by [`Menu::batch`], even if its function panics.
+#[cfg(target_os = "linux")]
+struct BatchGuard<'a> {
+    // `None` once the batches ended
+    menu: Option<&'a Menu>,
+    submenus: Vec<crate::Submenu>,
+}
+
+#[cfg(target_os = "linux")]
+impl BatchGuard<'_> {
+    fn end(&mut self) -> crate::Result<()> {
+        let Some(menu) = self.menu.take() else {
+            return Ok(());
+        };
+
+        let mut res = menu.inner.borrow_mut().end_batch();
+        for submenu in &self.submenus {
+            res = res.and(submenu.end_batch());
+        }
+        res
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.end();
+    }
 }
 
 impl Default for Menu {
@@ -27,6 +93,7 @@ impl Menu {
         Self {
             id: Rc::new(menu.id().clone()),
             inner: Rc::new(RefCell::new(menu)),
+            index: Default::default(),
         }
     }
 
@@ -36,6 +103,7 @@ impl Menu {
         Self {
             id: Rc::new(id.clone()),
             inner: Rc::new(RefCell::new(crate::platform_impl::Menu::new(Some(id)))),
+            index: Default::default(),
         }
     }
 
@@ -61,6 +129,11 @@ impl Menu {
         &self.id
     }
 
+    /// Whether `other` is this same menu.
+    pub(crate) fn ptr_eq(&self, other: &Menu) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
     /// Add a menu item to the end of this menu.
     ///
     /// ## Platform-spcific:
@@ -69,7 +142,7 @@ impl Menu {
     ///
     /// [`Submenu`]: crate::Submenu
     pub fn append(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
-        self.inner.borrow_mut().add_menu_item(item, AddOp::Append)
+        self.add_menu_item(item, AddOp::Append)
     }
 
     /// Add menu items to the end of this menu. It calls [`Menu::append`] in a loop internally.
@@ -95,9 +168,7 @@ impl Menu {
     ///
     /// [`Submenu`]: crate::Submenu
     pub fn prepend(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
-        self.inner
-            .borrow_mut()
-            .add_menu_item(item, AddOp::Insert(0))
+        self.add_menu_item(item, AddOp::Insert(0))
     }
 
     /// Add menu items to the beginning of this menu. It calls [`Menu::insert_items`] with position of `0` internally.
@@ -119,9 +190,15 @@ impl Menu {
     ///
     /// [`Submenu`]: crate::Submenu
     pub fn insert(&self, item: &dyn IsMenuItem, position: usize) -> crate::Result<()> {
-        self.inner
-            .borrow_mut()
-            .add_menu_item(item, AddOp::Insert(position))
+        self.add_menu_item(item, AddOp::Insert(position))
+    }
+
+    fn add_menu_item(&self, item: &dyn IsMenuItem, op: AddOp) -> crate::Result<()> {
+        self.check_unique_ids(item)?;
+        self.inner.borrow_mut().add_menu_item(item, op)?;
+        menu_parent::added_to(item, MenuParent::Menu(self));
+        menu_index::layout_changed(MenuParent::Menu(self));
+        Ok(())
     }
 
     /// Insert menu items at the specified `postion` in the menu.
@@ -141,7 +218,10 @@ impl Menu {
 
     /// Remove a menu item from this menu.
     pub fn remove(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
-        self.inner.borrow_mut().remove(item)
+        self.inner.borrow_mut().remove(item)?;
+        menu_parent::removed_from(item, MenuParent::Menu(self));
+        menu_index::layout_changed(MenuParent::Menu(self));
+        Ok(())
     }
 
     /// Remove the menu item at the specified position from this menu and returns it.
@@ -156,11 +236,214 @@ impl Menu {
         }
     }
 
+    /// Move a menu item of this menu to `position`, which is clamped to the last position.
+    ///
+    /// The native items are moved without being recreated.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS:** The item is removed and inserted again,
+    ///   and put back where it was if inserting it fails.
+    pub fn move_item(&self, item: &dyn IsMenuItem, position: usize) -> crate::Result<()> {
+        #[cfg(target_os = "linux")]
+        let res = self.inner.borrow_mut().move_item(item, position);
+        #[cfg(not(target_os = "linux"))]
+        let res = {
+            let mut inner = self.inner.borrow_mut();
+            let index = inner.items().iter().position(|i| i.id() == item.id());
+            inner.remove(item).and_then(|_| {
+                let position = position.min(inner.items().len());
+                let res = inner.add_menu_item(item, AddOp::Insert(position));
+                // put the item back where it was, like `move_item_to` does
+                if let (Err(_), Some(index)) = (&res, index) {
+                    let _ = inner.add_menu_item(item, AddOp::Insert(index));
+                }
+                res
+            })
+        };
+        menu_index::layout_changed(MenuParent::Menu(self));
+        res
+    }
+
+    /// Move a menu item of this menu to `position` in `parent`, another menu or submenu.
+    ///
+    /// The item is removed from this menu and inserted into `parent`, `position` is clamped
+    /// to the end of `parent`, and if `parent` is this menu, this is the same as [`Menu::move_item`].
+    /// Moving a submenu into itself or one of its own submenus returns [`Error::SubmenuIntoItself`].
+    ///
+    /// [`Error::SubmenuIntoItself`]: crate::Error::SubmenuIntoItself
+    pub fn move_item_to<'a>(
+        &self,
+        item: &dyn IsMenuItem,
+        parent: impl Into<MenuParent<'a>>,
+        position: usize,
+    ) -> crate::Result<()> {
+        MenuParent::from(self).move_item_to(item, parent.into(), position)
+    }
+
+    /// Remove all the menu items of this menu.
+    pub fn clear(&self) {
+        for item in self.items() {
+            let _ = self.remove(item.as_ref());
+        }
+    }
+
+    /// Replace all the menu items of this menu with `items`,
+    /// calling [`Menu::clear`] and [`Menu::append_items`] in a [`Menu::batch`].
+    ///
+    /// ## Platform-spcific:
+    ///
+    /// - **macOS:** Only [`Submenu`] can be added to the menu
+    ///
+    /// [`Submenu`]: crate::Submenu
+    pub fn replace_items(&self, items: &[&dyn IsMenuItem]) -> crate::Result<()> {
+        self.batch(|menu| {
+            menu.clear();
+            menu.append_items(items)
+        })?
+    }
+
+    /// Runs `f` with this menu, creating the native items of the items added to this menu
+    /// and its submenus once `f` returns, so that large updates cause a single relayout.
+    ///
+    /// Items removed in `f` are removed right away, and errors creating the native items
+    /// are returned after every item was added.
+    /// If `f` panics, the native items are still created while unwinding.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux**: Only the GTK3 menus are batched, the GTK4 menus
+    ///   already rebuild their menu models once per update.
+    /// - **Windows / macOS**: Runs `f` right away.
+    pub fn batch<R>(&self, f: impl FnOnce(&Menu) -> R) -> crate::Result<R> {
+        #[cfg(target_os = "linux")]
+        {
+            if !self.inner.borrow_mut().begin_batch() {
+                return Ok(f(self));
+            }
+
+            let mut guard = BatchGuard {
+                menu: Some(self),
+                // a submenu found twice is only started once
+                submenus: self
+                    .walk()
+                    .filter_map(|i| i.as_submenu().cloned())
+                    .filter(|s| s.begin_batch())
+                    .collect(),
+            };
+
+            let ret = f(self);
+
+            guard.end().map(|_| ret)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        Ok(f(self))
+    }
+
+    /// Require the items of this menu and of its submenus to have unique ids.
+    ///
+    /// While required, adding an item to this menu or to one of its submenus, at any depth,
+    /// returns [`Error::DuplicateId`] if the item, or an item of its submenus, has the same id
+    /// as another item already in this menu or its submenus. The same item can still be added
+    /// more than once. Items already in the menu aren't checked, use [`Menu::find_duplicate_id`] for that.
+    ///
+    /// [`Error::DuplicateId`]: crate::Error::DuplicateId
+    pub fn set_unique_ids(&self, unique: bool) {
+        self.index.borrow_mut().set_unique_ids(unique);
+    }
+
+    /// Returns the first id shared by two different items in this menu or its submenus.
+    pub fn find_duplicate_id(&self) -> Option<MenuId> {
+        self.index().find_duplicate_id()
+    }
+
     /// Returns a list of menu items that has been added to this menu.
     pub fn items(&self) -> Vec<MenuItemKind> {
         self.inner.borrow().items()
     }
 
+    /// Creates a copy of this menu and its items, recursively, that shares no state
+    /// with them, for example to show the same context menu for several documents.
+    ///
+    /// `id_mapper` is called with the id of the menu and of each item and returns
+    /// the id of its copy, or `None` to give it a new id, see [`Submenu::deep_clone`](crate::Submenu::deep_clone).
+    pub fn deep_clone<F: FnMut(&MenuId) -> Option<MenuId>>(
+        &self,
+        mut id_mapper: F,
+    ) -> crate::Result<Menu> {
+        let menu = match id_mapper(self.id()) {
+            Some(id) => Menu::with_id(id),
+            None => Menu::new(),
+        };
+        for item in self.items() {
+            menu.append(item.deep_clone(&mut id_mapper)?.as_ref())?;
+        }
+        menu.set_unique_ids(self.index.borrow().unique_ids());
+        Ok(menu)
+    }
+
+    /// Returns the first item with `id` in this menu or any of its submenus,
+    /// searched depth-first.
+    pub fn find_by_id(&self, id: &MenuId) -> Option<MenuItemKind> {
+        self.index().find(id)
+    }
+
+    /// Returns the ids of the submenus leading to the first item with `id`
+    /// in this menu or any of its submenus, starting with a direct child of this menu.
+    ///
+    /// The path is empty for direct children of this menu.
+    pub fn find_path_by_id(&self, id: &MenuId) -> Option<Vec<MenuId>> {
+        self.index().path(id)
+    }
+
+    /// Returns an iterator over all the items in this menu and its submenus, depth-first,
+    /// each submenu is followed by its own items.
+    pub fn walk(&self) -> impl Iterator<Item = MenuItemKind> {
+        self.index().items().to_vec().into_iter()
+    }
+
+    /// Returns [`Error::DuplicateId`](crate::Error::DuplicateId) if this menu requires unique ids
+    /// and `item` or its descendants have the id of another item of this menu.
+    pub(crate) fn check_unique_ids(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
+        let index = self.index();
+        if index.unique_ids() {
+            if let Some(id) = index.duplicate_id(&item.kind()) {
+                return Err(crate::Error::DuplicateId(id));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn invalidate_index(&self) {
+        self.index.borrow_mut().invalidate();
+    }
+
+    fn index(&self) -> std::cell::Ref<'_, MenuIndex> {
+        self.index.borrow_mut().refresh(|| self.items());
+        self.index.borrow()
+    }
+
+    /// Returns a `Send` and `Sync` handle to update the items of this menu from other threads.
+    ///
+    /// Must be called on the thread running the GTK main loop, see [`MenuHandle`](crate::MenuHandle).
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS:** Unsupported, this method is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn handle(&self) -> crate::MenuHandle {
+        crate::MenuHandle::new(self)
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakMenu {
+        WeakMenu {
+            id: self.id.clone(),
+            inner: Rc::downgrade(&self.inner),
+            index: Rc::downgrade(&self.index),
+        }
+    }
+
     /// Adds this menu to a [`gtk::Window`]
     ///
     /// - `container`: this is an optional paramter to specify a container for the [`gtk::MenuBar`],
@@ -189,9 +472,81 @@ impl Menu {
         W: gtk::prelude::IsA<gtk::Container>,
         C: gtk::prelude::IsA<gtk::Container>,
     {
-        self.inner
-            .borrow_mut()
-            .init_for_gtk_window(window, container)
+        crate::platform_impl::Menu::init_for_gtk_window(&self.inner, window, container)
+    }
+
+    /// Adds this menu to a [`gtk4::Window`] as a [`gtk4::PopoverMenuBar`].
+    ///
+    /// - `container`: an optional [`gtk4::Box`] the menubar is prepended to,
+    ///   otherwise the menubar is set as the child of the window.
+    ///
+    /// The accelerators of the menu items are added to the window as a [`gtk4::ShortcutController`].
+    ///
+    /// ## Panics:
+    ///
+    /// Panics if gtk hasn't been initialized on the thread.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn init_for_gtk4_window<W, C>(&self, window: &W, container: Option<&C>) -> crate::Result<()>
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+        C: gtk4::prelude::IsA<gtk4::Box>,
+    {
+        crate::platform_impl::gtk4::Gtk4Menu::init_for_window(&self.inner, window, container)
+    }
+
+    /// Adds this menu to a [`gtk::Window`], leaving the placement of its [`gtk::MenuBar`] to `place`.
+    ///
+    /// `place` is called with the new menubar, which isn't in any container yet,
+    /// and should add it to a container inside `window`, for example attach it to a [`gtk::Grid`]
+    /// or pack it into a [`gtk::HeaderBar`]. The menu items and accelerators are still managed
+    /// by the menu, and [`Menu::remove_for_gtk_window`] destroys the menubar wherever it was placed.
+    ///
+    /// ## Example:
+    /// ```no_run
+    /// # use gtk::prelude::*;
+    /// let window = gtk::Window::builder().build();
+    /// let grid = gtk::Grid::new();
+    /// let menu = muda::Menu::new();
+    /// // -- snip, add your menu items --
+    /// menu.init_for_gtk_container(&window, |menu_bar| grid.attach(menu_bar, 0, 0, 2, 1));
+    /// ```
+    ///
+    /// ## Panics:
+    ///
+    /// Panics if the gtk event loop hasn't been initialized on the thread.
+    #[cfg(target_os = "linux")]
+    pub fn init_for_gtk_container<W, F>(&self, window: &W, place: F) -> crate::Result<()>
+    where
+        W: gtk::prelude::IsA<gtk::Window>,
+        F: FnOnce(&gtk::MenuBar),
+    {
+        let menu_bar =
+            crate::platform_impl::Menu::create_menubar_for_gtk_window(&self.inner, window)?;
+        place(&menu_bar);
+        Ok(())
+    }
+
+    /// Adds this menu to a [`gtk4::Window`], leaving the placement of its
+    /// [`gtk4::PopoverMenuBar`] to `place`.
+    ///
+    /// `place` is called with the new menubar, which has no parent yet,
+    /// and should add it to a widget inside `window`, for example attach it to a [`gtk4::Grid`]
+    /// or pack it into a [`gtk4::HeaderBar`]. The menu items and shortcuts are still managed
+    /// by the menu, and [`Menu::remove_for_gtk4_window`] removes the menubar from its parent.
+    ///
+    /// ## Panics:
+    ///
+    /// Panics if gtk hasn't been initialized on the thread.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn init_for_gtk4_container<W, F>(&self, window: &W, place: F) -> crate::Result<()>
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+        F: FnOnce(&gtk4::PopoverMenuBar),
+    {
+        let menu_bar =
+            crate::platform_impl::gtk4::Gtk4Menu::create_menubar_for_window(&self.inner, window)?;
+        place(&menu_bar);
+        Ok(())
     }
 
     /// Adds this menu to a win32 window.
@@ -278,6 +633,15 @@ impl Menu {
         self.inner.borrow_mut().remove_for_gtk_window(window)
     }
 
+    /// Removes this menu from a [`gtk4::Window`]
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn remove_for_gtk4_window<W>(&self, window: &W) -> crate::Result<()>
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+    {
+        self.inner.borrow_mut().gtk4.remove_for_window(window)
+    }
+
     /// Removes this menu from a win32 window
     ///
     /// # Safety
@@ -297,6 +661,15 @@ impl Menu {
         self.inner.borrow_mut().hide_for_gtk_window(window)
     }
 
+    /// Hides this menu from a [`gtk4::Window`]
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn hide_for_gtk4_window<W>(&self, window: &W) -> crate::Result<()>
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+    {
+        self.inner.borrow().gtk4.hide_for_window(window)
+    }
+
     /// Hides this menu from a win32 window
     ///
     /// # Safety
@@ -316,6 +689,15 @@ impl Menu {
         self.inner.borrow_mut().show_for_gtk_window(window)
     }
 
+    /// Shows this menu on a [`gtk4::Window`]
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn show_for_gtk4_window<W>(&self, window: &W) -> crate::Result<()>
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+    {
+        self.inner.borrow().gtk4.show_for_window(window)
+    }
+
     /// Shows this menu on a win32 window
     ///
     /// # Safety
@@ -335,6 +717,15 @@ impl Menu {
         self.inner.borrow().is_visible_on_gtk_window(window)
     }
 
+    /// Returns whether this menu visible on a [`gtk4::Window`]
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn is_visible_on_gtk4_window<W>(&self, window: &W) -> bool
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+    {
+        self.inner.borrow().gtk4.is_visible_on_window(window)
+    }
+
     #[cfg(target_os = "linux")]
     /// Returns the [`gtk::MenuBar`] that is associated with this window if it exists.
     /// This is useful to get information about the menubar for example its height.
@@ -345,6 +736,89 @@ impl Menu {
         self.inner.borrow().gtk_menubar_for_gtk_window(window)
     }
 
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    /// Returns the [`gtk4::PopoverMenuBar`] that is associated with this window if it exists.
+    /// This is useful to get information about the menubar for example its height.
+    pub fn gtk4_menubar_for_gtk4_window<W>(&self, window: &W) -> Option<gtk4::PopoverMenuBar>
+    where
+        W: gtk4::prelude::IsA<gtk4::Window>,
+    {
+        self.inner.borrow().gtk4.menubar_for_window(window)
+    }
+
+    /// Exports this menu as a [`gio::Menu`](gtk::gio::Menu) and the [`gio::SimpleActionGroup`](gtk::gio::SimpleActionGroup)
+    /// its items activate, for use with [`GtkApplicationExt::set_menubar`](gtk::prelude::GtkApplicationExt::set_menubar) or [`gtk::PopoverMenu`].
+    ///
+    /// The action group must be inserted with the name `"muda"`, activating an item
+    /// emits a [`MenuEvent`](crate::MenuEvent) the same way menus created by muda do.
+    /// Check menu items are exported as stateful actions, separators split the items into sections
+    /// and accelerators are registered on the default [`gtk::Application`], if there is one.
+    ///
+    /// The model stays in sync with later changes to this menu and its items,
+    /// calling this function again returns the same model and action group.
+    ///
+    /// ## Example:
+    /// ```no_run
+    /// # use gtk::prelude::*;
+    /// let app = gtk::Application::builder().build();
+    /// let window = gtk::ApplicationWindow::new(&app);
+    /// let menu = muda::Menu::new();
+    /// // -- snip, add your menu items --
+    /// let (model, actions) = menu.to_gio_menu_model();
+    /// app.set_menubar(Some(&model));
+    /// window.insert_action_group("muda", Some(&actions));
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn to_gio_menu_model(&self) -> (gtk::gio::Menu, gtk::gio::SimpleActionGroup) {
+        crate::platform_impl::Menu::to_gio_menu_model(&self.inner)
+    }
+
+    /// Returns the [`gio::Menu`](gtk4::gio::Menu) this menu is displayed from
+    /// and the [`gio::SimpleActionGroup`](gtk4::gio::SimpleActionGroup) its items activate,
+    /// the action group must be inserted with the name `"muda"`.
+    ///
+    /// The model stays in sync with later changes to this menu and its items.
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    pub fn to_gtk4_gio_menu_model(&self) -> (gtk4::gio::Menu, gtk4::gio::SimpleActionGroup) {
+        crate::platform_impl::gtk4::Gtk4Menu::to_gio_menu_model(&self.inner)
+    }
+
+    /// Publishes this menu on the session bus at `object_path` using the
+    /// `com.canonical.dbusmenu` protocol, so it can be shown by a status notifier
+    /// host or a global menu bar.
+    ///
+    /// The returned [`DBusMenuServer`](crate::DBusMenuServer) keeps the menu published
+    /// and in sync with later changes to its items, activating an item from a client
+    /// emits a [`MenuEvent`](crate::MenuEvent) the same way menus created by muda do.
+    ///
+    /// Requires the `dbusmenu` feature.
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    pub fn serve_dbusmenu(&self, object_path: &str) -> crate::Result<crate::DBusMenuServer> {
+        self.serve_dbusmenu_inner(None, object_path)
+    }
+
+    /// Publishes this menu at `object_path` on `connection`, see [`Menu::serve_dbusmenu`].
+    ///
+    /// Requires the `dbusmenu` feature.
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    pub fn serve_dbusmenu_on_connection(
+        &self,
+        connection: &gtk::gio::DBusConnection,
+        object_path: &str,
+    ) -> crate::Result<crate::DBusMenuServer> {
+        self.serve_dbusmenu_inner(Some(connection), object_path)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "dbusmenu"))]
+    fn serve_dbusmenu_inner(
+        &self,
+        connection: Option<&gtk::gio::DBusConnection>,
+        object_path: &str,
+    ) -> crate::Result<crate::DBusMenuServer> {
+        let root = crate::platform_impl::DBusMenuRoot::Menu(Rc::downgrade(&self.inner));
+        crate::DBusMenuServer::serve(root, connection, object_path)
+    }
+
     /// Returns whether this menu visible on a on a win32 window
     ///
     /// # Safety
@@ -397,9 +871,95 @@ impl ContextMenu for Menu {
         window: &gtk::Window,
         position: Option<Position>,
     ) -> bool {
-        self.inner
-            .borrow_mut()
-            .show_context_menu_for_gtk_window(window, position)
+        self.show_context_menu_for_gtk_window_with_options(window, ContextMenuOptions::at(position))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_callback(
+        &self,
+        window: &gtk::Window,
+        position: Option<Position>,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        self.show_context_menu_for_gtk_window_with_options_and_callback(
+            window,
+            ContextMenuOptions::at(position),
+            callback,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_options(
+        &self,
+        window: &gtk::Window,
+        options: ContextMenuOptions,
+    ) -> bool {
+        crate::platform_impl::show_context_menu(self.gtk_context_menu(), window, &options)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn show_context_menu_for_gtk_window_with_options_and_callback(
+        &self,
+        window: &gtk::Window,
+        options: ContextMenuOptions,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        crate::platform_impl::show_context_menu_with_callback(
+            self.gtk_context_menu(),
+            window,
+            &options,
+            callback,
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window(
+        &self,
+        window: &gtk4::Window,
+        position: Option<Position>,
+    ) -> bool {
+        self.show_context_menu_for_gtk4_window_with_options(
+            window,
+            ContextMenuOptions::at(position),
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_callback(
+        &self,
+        window: &gtk4::Window,
+        position: Option<Position>,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        self.show_context_menu_for_gtk4_window_with_options_and_callback(
+            window,
+            ContextMenuOptions::at(position),
+            callback,
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_options(
+        &self,
+        window: &gtk4::Window,
+        options: ContextMenuOptions,
+    ) -> bool {
+        crate::platform_impl::gtk4::show_context_menu(&self.gtk4_context_menu(), window, &options)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn show_context_menu_for_gtk4_window_with_options_and_callback(
+        &self,
+        window: &gtk4::Window,
+        options: ContextMenuOptions,
+        callback: Box<dyn FnOnce(bool)>,
+    ) {
+        crate::platform_impl::gtk4::show_context_menu_with_callback(
+            &self.gtk4_context_menu(),
+            window,
+            &options,
+            callback,
+        )
     }
 
     #[cfg(target_os = "linux")]
@@ -407,6 +967,11 @@ impl ContextMenu for Menu {
         self.inner.borrow_mut().gtk_context_menu()
     }
 
+    #[cfg(all(target_os = "linux", feature = "gtk4"))]
+    fn gtk4_context_menu(&self) -> gtk4::PopoverMenu {
+        crate::platform_impl::gtk4::Gtk4Menu::context_menu(&self.inner)
+    }
+
     #[cfg(target_os = "macos")]
     unsafe fn show_context_menu_for_nsview(
         &self,
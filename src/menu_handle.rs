@@ -0,0 +1,294 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use gtk::glib;
+
+use crate::{
+    accelerator::Accelerator, icon::Icon, menu::WeakMenu, util::Counter, CheckMenuItemBuilder,
+    IconMenuItemBuilder, Menu, MenuId, MenuItemBuilder, MenuItemKind,
+};
+
+static COUNTER: Counter = Counter::new();
+
+thread_local! {
+    /// The menus of the handles created on this thread, by handle key.
+    static MENUS: RefCell<HashMap<u32, WeakMenu>> = RefCell::new(HashMap::new());
+}
+
+/// A `Send` and `Sync` handle to a [`Menu`], created with [`Menu::handle`],
+/// to update its items from other threads.
+///
+/// Operations are queued on the GLib main context of the thread the handle was
+/// created on, using [`glib::MainContext::spawn`], and applied in order once that
+/// thread iterates its main loop, even if it didn't start it yet.
+///
+/// Items are looked up by id anywhere in the menu and its submenus, see [`Menu::find_by_id`].
+/// Operations on ids that aren't found or on items that don't support them are ignored,
+/// as are operations queued after the menu was dropped.
+///
+/// ## Platform-specific:
+///
+/// - **Windows / macOS:** Unsupported, this type is only available on Linux.
+#[derive(Clone)]
+pub struct MenuHandle {
+    inner: Arc<HandleInner>,
+}
+
+struct HandleInner {
+    key: u32,
+    context: glib::MainContext,
+}
+
+impl Drop for HandleInner {
+    fn drop(&mut self) {
+        let key = self.key;
+        self.context.spawn(async move {
+            let _ = MENUS.try_with(|menus| menus.borrow_mut().remove(&key));
+        });
+    }
+}
+
+impl std::fmt::Debug for MenuHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MenuHandle")
+            .field("key", &self.inner.key)
+            .finish()
+    }
+}
+
+/// A new item to add with [`MenuHandle::append`].
+#[derive(Debug, Clone)]
+pub enum MenuHandleItem {
+    MenuItem(MenuItemBuilder),
+    Check(CheckMenuItemBuilder),
+    Icon(IconMenuItemBuilder),
+}
+
+impl From<MenuItemBuilder> for MenuHandleItem {
+    fn from(builder: MenuItemBuilder) -> Self {
+        Self::MenuItem(builder)
+    }
+}
+
+impl From<CheckMenuItemBuilder> for MenuHandleItem {
+    fn from(builder: CheckMenuItemBuilder) -> Self {
+        Self::Check(builder)
+    }
+}
+
+impl From<IconMenuItemBuilder> for MenuHandleItem {
+    fn from(builder: IconMenuItemBuilder) -> Self {
+        Self::Icon(builder)
+    }
+}
+
+impl MenuHandle {
+    pub(crate) fn new(menu: &Menu) -> Self {
+        Self::with_context(menu, glib::MainContext::ref_thread_default())
+    }
+
+    /// Creates a handle whose operations are applied when `context` is iterated,
+    /// which must only be on the current thread.
+    fn with_context(menu: &Menu, context: glib::MainContext) -> Self {
+        let key = COUNTER.next();
+        MENUS.with(|menus| {
+            let mut menus = menus.borrow_mut();
+            menus.retain(|_, menu| menu.upgrade().is_some());
+            menus.insert(key, menu.downgrade());
+        });
+
+        Self {
+            inner: Arc::new(HandleInner { key, context }),
+        }
+    }
+
+    /// Queues `f` to run with the menu on the thread the handle was created on.
+    ///
+    /// Unlike `MainContext::invoke`, which runs `f` right away on the calling thread
+    /// when no thread owns the context yet, `f` always waits for the context to be iterated.
+    fn queue(&self, f: impl FnOnce(&Menu) + Send + 'static) {
+        let key = self.inner.key;
+        self.inner.context.spawn(async move {
+            let menu = MENUS.with(|menus| menus.borrow().get(&key).and_then(WeakMenu::upgrade));
+            if let Some(menu) = menu {
+                f(&menu);
+            }
+        });
+    }
+
+    /// Queues changing the text of the item with `id`.
+    pub fn set_text<I: Into<MenuId>, S: Into<String>>(&self, id: I, text: S) {
+        let id = id.into();
+        let text = text.into();
+        self.queue(move |menu| match menu.find_by_id(&id) {
+            Some(MenuItemKind::MenuItem(i)) => i.set_text(text),
+            Some(MenuItemKind::Submenu(i)) => i.set_text(text),
+            Some(MenuItemKind::Predefined(i)) => i.set_text(text),
+            Some(MenuItemKind::Check(i)) => i.set_text(text),
+            Some(MenuItemKind::Icon(i)) => i.set_text(text),
+            None => {}
+        });
+    }
+
+    /// Queues enabling or disabling the item with `id`.
+    pub fn set_enabled<I: Into<MenuId>>(&self, id: I, enabled: bool) {
+        let id = id.into();
+        self.queue(move |menu| match menu.find_by_id(&id) {
+            Some(MenuItemKind::MenuItem(i)) => i.set_enabled(enabled),
+            Some(MenuItemKind::Submenu(i)) => i.set_enabled(enabled),
+            Some(MenuItemKind::Check(i)) => i.set_enabled(enabled),
+            Some(MenuItemKind::Icon(i)) => i.set_enabled(enabled),
+            _ => {}
+        });
+    }
+
+    /// Queues checking or unchecking the check menu item with `id`.
+    pub fn set_checked<I: Into<MenuId>>(&self, id: I, checked: bool) {
+        let id = id.into();
+        self.queue(move |menu| {
+            if let Some(MenuItemKind::Check(i)) = menu.find_by_id(&id) {
+                i.set_checked(checked)
+            }
+        });
+    }
+
+    /// Queues changing or removing the icon of the icon menu item with `id`.
+    pub fn set_icon<I: Into<MenuId>>(&self, id: I, icon: Option<Icon>) {
+        let id = id.into();
+        self.queue(move |menu| {
+            if let Some(MenuItemKind::Icon(i)) = menu.find_by_id(&id) {
+                i.set_icon(icon)
+            }
+        });
+    }
+
+    /// Queues changing or removing the accelerator of the item with `id`.
+    pub fn set_accelerator<I: Into<MenuId>>(&self, id: I, accelerator: Option<Accelerator>) {
+        let id = id.into();
+        self.queue(move |menu| {
+            let _ = match menu.find_by_id(&id) {
+                Some(MenuItemKind::MenuItem(i)) => i.set_accelerator(accelerator),
+                Some(MenuItemKind::Check(i)) => i.set_accelerator(accelerator),
+                Some(MenuItemKind::Icon(i)) => i.set_accelerator(accelerator),
+                _ => Ok(()),
+            };
+        });
+    }
+
+    /// Queues building `item` and adding it to the end of the submenu with id `parent`,
+    /// or of the menu itself if `None`.
+    pub fn append(&self, parent: Option<&MenuId>, item: impl Into<MenuHandleItem>) {
+        let parent = parent.cloned();
+        let item = item.into();
+        self.queue(move |menu| {
+            let item = match item {
+                MenuHandleItem::MenuItem(b) => MenuItemKind::MenuItem(b.build()),
+                MenuHandleItem::Check(b) => MenuItemKind::Check(b.build()),
+                MenuHandleItem::Icon(b) => MenuItemKind::Icon(b.build()),
+            };
+            let _ = match parent {
+                None => menu.append(item.as_ref()),
+                Some(parent) => match menu.find_by_id(&parent) {
+                    Some(MenuItemKind::Submenu(submenu)) => submenu.append(item.as_ref()),
+                    _ => Ok(()),
+                },
+            };
+        });
+    }
+
+    /// Queues removing the item with `id` from its parent menu or submenu.
+    pub fn remove<I: Into<MenuId>>(&self, id: I) {
+        let id = id.into();
+        self.queue(move |menu| {
+            let (Some(item), Some(path)) = (menu.find_by_id(&id), menu.find_path_by_id(&id)) else {
+                return;
+            };
+            let _ = match path.last() {
+                None => menu.remove(item.as_ref()),
+                Some(parent) => match menu.find_by_id(parent) {
+                    Some(MenuItemKind::Submenu(submenu)) => submenu.remove(item.as_ref()),
+                    _ => Ok(()),
+                },
+            };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckMenuItem, MenuItem, Submenu};
+
+    #[test]
+    fn operations_are_applied_on_the_menu_thread() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let context = glib::MainContext::new();
+        context
+            .with_thread_default(|| {
+                let item = MenuItem::with_id("item", "Item", true, None);
+                let check = CheckMenuItem::with_id("check", "Check", true, false, None);
+                let file =
+                    Submenu::with_id_and_items("file", "File", true, &[&item, &check]).unwrap();
+                let menu = Menu::with_items(&[&file]).unwrap();
+
+                let handle = menu.handle();
+                assert_send_sync(&handle);
+                std::thread::spawn(move || {
+                    handle.set_text("item", "Renamed");
+                    handle.set_enabled("item", false);
+                    handle.set_checked("check", true);
+                    handle.append(
+                        Some(&MenuId::new("file")),
+                        MenuItemBuilder::new().id(MenuId::new("new")).text("New"),
+                    );
+                    handle.remove("check");
+                    handle.set_text("missing", "Ignored");
+                })
+                .join()
+                .unwrap();
+
+                while context.iteration(false) {}
+
+                assert_eq!(item.text(), "Renamed");
+                assert!(!item.is_enabled());
+                assert!(check.is_checked());
+                assert_eq!(
+                    file.items()
+                        .iter()
+                        .map(|i| i.id().clone())
+                        .collect::<Vec<_>>(),
+                    [MenuId::new("item"), MenuId::new("new")]
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn operations_wait_for_the_menu_thread_loop() {
+        let item = MenuItem::with_id("item", "Item", true, None);
+        let menu = Menu::with_items(&[&item]).unwrap();
+
+        // no thread owns the context until it is iterated below
+        let context = glib::MainContext::new();
+        let handle = MenuHandle::with_context(&menu, context.clone());
+        std::thread::spawn(move || {
+            handle.set_text("item", "Renamed");
+            handle.set_enabled("item", false);
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(item.text(), "Item");
+        assert!(item.is_enabled());
+
+        let _guard = context.acquire().unwrap();
+        while context.iteration(false) {}
+
+        assert_eq!(item.text(), "Renamed");
+        assert!(!item.is_enabled());
+    }
+}
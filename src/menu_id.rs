@@ -1,4 +1,11 @@
-use std::{convert::Infallible, str::FromStr};
+use std::{
+    convert::Infallible,
+    str::FromStr,
+    sync::{PoisonError, RwLock},
+};
+
+/// The prefix of the ids generated for items created without one.
+static AUTO_ID_PREFIX: RwLock<String> = RwLock::new(String::new());
 
 /// An unique id that is associated with a menu or a menu item.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
@@ -10,6 +17,40 @@ impl MenuId {
     pub fn new<S: AsRef<str>>(id: S) -> Self {
         Self(id.as_ref().to_string())
     }
+
+    /// Create a new menu id from a typed value, for example a variant of an enum of commands
+    /// implementing `Into<&'static str>`, which can be converted back with [`MenuId::to_typed`].
+    pub fn typed<E: Into<&'static str>>(value: E) -> Self {
+        Self::new(value.into())
+    }
+
+    /// Convert this id back to the typed value it was created from with [`MenuId::typed`],
+    /// or `None` if it isn't one.
+    pub fn to_typed<E: for<'a> TryFrom<&'a str>>(&self) -> Option<E> {
+        E::try_from(&self.0).ok()
+    }
+
+    /// Set the prefix of the ids generated for menus and menu items created without one,
+    /// for example `"muda-"` to generate `"muda-1"`, `"muda-2"` and so on,
+    /// so they can't collide with your own ids. Only applies to the menus and menu items
+    /// created after calling it, and defaults to no prefix.
+    pub fn set_auto_id_prefix<S: Into<String>>(prefix: S) {
+        *AUTO_ID_PREFIX
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = prefix.into();
+    }
+
+    /// Create the id of a menu or menu item created without one.
+    pub(crate) fn auto(n: u32) -> Self {
+        let prefix = AUTO_ID_PREFIX
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        Self::auto_with_prefix(&prefix, n)
+    }
+
+    fn auto_with_prefix(prefix: &str, n: u32) -> Self {
+        Self(format!("{prefix}{n}"))
+    }
 }
 
 impl AsRef<str> for MenuId {
@@ -82,4 +123,46 @@ mod test {
         assert_eq!(&MenuId::new("t"), &MenuId::new("t"));
         assert_eq!(MenuId::new("t").as_ref(), "t");
     }
+
+    #[test]
+    fn typed_ids_round_trip() {
+        #[derive(Debug, PartialEq)]
+        enum Command {
+            Open,
+            Save,
+        }
+
+        impl From<Command> for &'static str {
+            fn from(command: Command) -> Self {
+                match command {
+                    Command::Open => "open",
+                    Command::Save => "save",
+                }
+            }
+        }
+
+        impl TryFrom<&str> for Command {
+            type Error = ();
+
+            fn try_from(id: &str) -> Result<Self, ()> {
+                match id {
+                    "open" => Ok(Command::Open),
+                    "save" => Ok(Command::Save),
+                    _ => Err(()),
+                }
+            }
+        }
+
+        let id = MenuId::typed(Command::Save);
+        assert_eq!(id, "save");
+        assert_eq!(id.to_typed(), Some(Command::Save));
+        assert_eq!(MenuId::new("quit").to_typed::<Command>(), None);
+    }
+
+    // the prefix is global, so it isn't set here while other tests create items
+    #[test]
+    fn auto_ids_use_the_prefix() {
+        assert_eq!(MenuId::auto_with_prefix("test-", 7), "test-7");
+        assert_eq!(MenuId::auto_with_prefix("", 7), "7");
+    }
 }
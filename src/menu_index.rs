@@ -0,0 +1,229 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    rc::Rc,
+};
+
+use crate::{menu_parent, MenuId, MenuItemKind, MenuParent, Submenu};
+
+/// Invalidates the [`MenuIndex`] of every menu `parent` is in, or of `parent` itself,
+/// must be called after an item is added to or removed from a menu or a submenu.
+pub(crate) fn layout_changed(parent: MenuParent) {
+    match parent {
+        MenuParent::Menu(menu) => menu.invalidate_index(),
+        MenuParent::Submenu(submenu) => {
+            let menus = menu_parent::root_menus(&submenu.inner.borrow());
+            for menu in menus {
+                menu.invalidate_index();
+            }
+        }
+    }
+}
+
+/// An index of all the descendants of a menu, rebuilt on the first
+/// lookup after the layout of the menu changed.
+#[derive(Default)]
+pub(crate) struct MenuIndex {
+    built: bool,
+    // all the descendants, depth-first
+    items: Vec<MenuItemKind>,
+    // the position in `items` and the parent submenus of the first item with each id
+    ids: HashMap<MenuId, (usize, Vec<MenuId>)>,
+    // whether adding an item with the id of another item is an error, see `Menu::set_unique_ids`
+    unique_ids: bool,
+}
+
+impl MenuIndex {
+    /// Creates an index of `children` and their descendants.
+    pub(crate) fn new(children: Vec<MenuItemKind>) -> Self {
+        let mut index = Self::default();
+        index.refresh(|| children);
+        index
+    }
+
+    /// Rebuilds the index from `children` if it was invalidated since it was last built.
+    pub(crate) fn refresh(&mut self, children: impl FnOnce() -> Vec<MenuItemKind>) {
+        if self.built {
+            return;
+        }
+
+        self.add_items(children(), &mut Vec::new());
+        self.built = true;
+    }
+
+    /// Clears the index, so it doesn't keep removed items alive, until the next lookup rebuilds it.
+    pub(crate) fn invalidate(&mut self) {
+        self.items.clear();
+        self.ids.clear();
+        self.built = false;
+    }
+
+    fn add_items(&mut self, items: Vec<MenuItemKind>, path: &mut Vec<Submenu>) {
+        for item in items {
+            if let Entry::Vacant(e) = self.ids.entry(item.id().clone()) {
+                e.insert((
+                    self.items.len(),
+                    path.iter().map(|s| s.id().clone()).collect(),
+                ));
+            }
+
+            let submenu = item.as_submenu().cloned();
+            self.items.push(item);
+
+            // a submenu that is its own descendant would never end
+            if let Some(submenu) =
+                submenu.filter(|s| !path.iter().any(|p| Rc::ptr_eq(&p.inner, &s.inner)))
+            {
+                let items = submenu.items();
+                path.push(submenu);
+                self.add_items(items, path);
+                path.pop();
+            }
+        }
+    }
+
+    pub(crate) fn unique_ids(&self) -> bool {
+        self.unique_ids
+    }
+
+    pub(crate) fn set_unique_ids(&mut self, unique: bool) {
+        self.unique_ids = unique;
+    }
+
+    /// Returns the first id of `item` or its descendants that is
+    /// the id of another item in this index or in `item`.
+    pub(crate) fn duplicate_id(&self, item: &MenuItemKind) -> Option<MenuId> {
+        let added = MenuIndex::new(vec![item.clone()]);
+        added
+            .items
+            .iter()
+            .find(|i| self.is_duplicate(i) || added.is_duplicate(i))
+            .map(|i| i.id().clone())
+    }
+
+    /// Returns the first id shared by two different items in this index.
+    pub(crate) fn find_duplicate_id(&self) -> Option<MenuId> {
+        self.items
+            .iter()
+            .find(|i| self.is_duplicate(i))
+            .map(|i| i.id().clone())
+    }
+
+    /// Whether the first item with the id of `item` is another item.
+    fn is_duplicate(&self, item: &MenuItemKind) -> bool {
+        self.ids
+            .get(item.id())
+            .is_some_and(|(i, _)| !self.items[*i].ptr_eq(item))
+    }
+
+    pub(crate) fn items(&self) -> &[MenuItemKind] {
+        &self.items
+    }
+
+    pub(crate) fn find(&self, id: &MenuId) -> Option<MenuItemKind> {
+        self.ids.get(id).map(|(i, _)| self.items[*i].clone())
+    }
+
+    pub(crate) fn path(&self, id: &MenuId) -> Option<Vec<MenuId>> {
+        self.ids.get(id).map(|(_, path)| path.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{Error, Menu, MenuId, MenuItem, Submenu};
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn finds_nested_items_and_follows_changes() {
+        let open = MenuItem::with_id("open", "Open", true, None);
+        let recent = Submenu::with_id_and_items("recent", "Recent", true, &[&open]).unwrap();
+        let file = Submenu::with_id_and_items("file", "File", true, &[&recent]).unwrap();
+        let menu = Menu::with_items(&[&file]).unwrap();
+
+        let id = MenuId::new("open");
+        assert_eq!(menu.find_by_id(&id).unwrap().id(), &id);
+        assert_eq!(
+            menu.find_path_by_id(&id),
+            Some(vec![MenuId::new("file"), MenuId::new("recent")])
+        );
+        assert_eq!(menu.find_path_by_id(file.id()), Some(vec![]));
+        assert_eq!(
+            menu.walk().map(|i| i.id().clone()).collect::<Vec<_>>(),
+            ["file", "recent", "open"].map(MenuId::new)
+        );
+
+        // changes to nested submenus are picked up
+        let save = MenuItem::with_id("save", "Save", true, None);
+        file.append(&save).unwrap();
+        assert!(menu.find_by_id(save.id()).is_some());
+        recent.remove(&open).unwrap();
+        assert!(menu.find_by_id(&id).is_none());
+        assert!(menu.find_path_by_id(&id).is_none());
+    }
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn walks_submenus_sharing_an_id() {
+        let deep = MenuItem::with_id("deep", "Deep", true, None);
+        let inner = Submenu::with_id_and_items("more", "More", true, &[&deep]).unwrap();
+        let outer = Submenu::with_id_and_items("more", "More", true, &[&inner]).unwrap();
+        let menu = Menu::with_items(&[&outer]).unwrap();
+
+        assert_eq!(
+            menu.find_path_by_id(deep.id()),
+            Some(vec![MenuId::new("more"), MenuId::new("more")])
+        );
+        assert_eq!(menu.walk().count(), 3);
+    }
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn releases_removed_items() {
+        let open = MenuItem::with_id("open", "Open", true, None);
+        let file = Submenu::with_id_and_items("file", "File", true, &[&open]).unwrap();
+        let menu = Menu::with_items(&[&file]).unwrap();
+        assert!(menu.find_by_id(open.id()).is_some());
+
+        let weak = Rc::downgrade(&open.inner);
+        file.remove(&open).unwrap();
+        drop(open);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    #[cfg_attr(all(miri, not(target_os = "linux")), ignore)]
+    fn rejects_duplicate_ids_when_required() {
+        let open = MenuItem::with_id("open", "Open", true, None);
+        let file = Submenu::with_id_and_items("file", "File", true, &[&open]).unwrap();
+        let menu = Menu::with_items(&[&file]).unwrap();
+        menu.set_unique_ids(true);
+
+        // the same item can be added again
+        menu.append(&open).unwrap();
+
+        let other = MenuItem::with_id("open", "Open", true, None);
+        assert!(matches!(menu.append(&other), Err(Error::DuplicateId(id)) if id == "open"));
+        let edit = Submenu::with_id_and_items("edit", "Edit", true, &[&other]).unwrap();
+        assert!(matches!(menu.append(&edit), Err(Error::DuplicateId(id)) if id == "open"));
+        assert_eq!(menu.items().len(), 2);
+
+        // items added to submenus are checked too, at any depth
+        assert!(matches!(file.append(&other), Err(Error::DuplicateId(id)) if id == "open"));
+        let recent = Submenu::with_id("recent", "Recent", true);
+        file.append(&recent).unwrap();
+        assert!(matches!(recent.append(&other), Err(Error::DuplicateId(id)) if id == "open"));
+        recent.append(&open).unwrap();
+        assert_eq!(menu.find_duplicate_id(), None);
+
+        menu.set_unique_ids(false);
+        file.append(&other).unwrap();
+        assert_eq!(menu.find_duplicate_id(), Some(MenuId::new("open")));
+        menu.append(&edit).unwrap();
+    }
+}
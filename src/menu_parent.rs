@@ -0,0 +1,271 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    menu::WeakMenu, platform_impl::MenuChild, IsMenuItem, Menu, MenuId, MenuItemKind, Submenu,
+};
+
+/// A [`Menu`] or a [`Submenu`] that items can be added to,
+/// see [`Menu::move_item_to`] and [`Submenu::move_item_to`].
+#[derive(Clone, Copy)]
+pub enum MenuParent<'a> {
+    Menu(&'a Menu),
+    Submenu(&'a Submenu),
+}
+
+impl<'a> From<&'a Menu> for MenuParent<'a> {
+    fn from(menu: &'a Menu) -> Self {
+        Self::Menu(menu)
+    }
+}
+
+impl<'a> From<&'a Submenu> for MenuParent<'a> {
+    fn from(submenu: &'a Submenu) -> Self {
+        Self::Submenu(submenu)
+    }
+}
+
+impl MenuParent<'_> {
+    /// Returns the id of this menu or submenu.
+    pub fn id(&self) -> &MenuId {
+        match self {
+            MenuParent::Menu(m) => m.id(),
+            MenuParent::Submenu(s) => s.id(),
+        }
+    }
+
+    pub(crate) fn items(&self) -> Vec<MenuItemKind> {
+        match self {
+            MenuParent::Menu(m) => m.items(),
+            MenuParent::Submenu(s) => s.items(),
+        }
+    }
+
+    fn insert(&self, item: &dyn IsMenuItem, position: usize) -> crate::Result<()> {
+        match self {
+            MenuParent::Menu(m) => m.insert(item, position),
+            MenuParent::Submenu(s) => s.insert(item, position),
+        }
+    }
+
+    fn remove(&self, item: &dyn IsMenuItem) -> crate::Result<()> {
+        match self {
+            MenuParent::Menu(m) => m.remove(item),
+            MenuParent::Submenu(s) => s.remove(item),
+        }
+    }
+
+    pub(crate) fn to_kind(self) -> MenuParentKind {
+        match self {
+            MenuParent::Menu(m) => MenuParentKind::Menu(m.clone()),
+            MenuParent::Submenu(s) => MenuParentKind::Submenu(s.clone()),
+        }
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakMenuParent {
+        match self {
+            MenuParent::Menu(m) => WeakMenuParent::Menu(m.downgrade()),
+            MenuParent::Submenu(s) => {
+                WeakMenuParent::Submenu(s.id.clone(), Rc::downgrade(&s.inner))
+            }
+        }
+    }
+
+    pub(crate) fn is_same(&self, other: &MenuParent) -> bool {
+        match (self, other) {
+            (MenuParent::Menu(a), MenuParent::Menu(b)) => a.ptr_eq(b),
+            (MenuParent::Submenu(a), MenuParent::Submenu(b)) => Rc::ptr_eq(&a.inner, &b.inner),
+            _ => false,
+        }
+    }
+
+    /// Whether this is `submenu` or one of its descendants.
+    fn is_within(&self, submenu: &Submenu) -> bool {
+        let MenuParent::Submenu(target) = self else {
+            return false;
+        };
+
+        let mut visited = Vec::new();
+        let mut stack = vec![submenu.clone()];
+        while let Some(submenu) = stack.pop() {
+            if Rc::ptr_eq(&submenu.inner, &target.inner) {
+                return true;
+            }
+            if visited
+                .iter()
+                .any(|s: &Submenu| Rc::ptr_eq(&s.inner, &submenu.inner))
+            {
+                continue;
+            }
+            stack.extend(
+                submenu
+                    .items()
+                    .iter()
+                    .filter_map(|i| i.as_submenu().cloned()),
+            );
+            visited.push(submenu);
+        }
+
+        false
+    }
+
+    /// Moves `item`, a child of this menu or submenu, to `position` in `to`.
+    ///
+    /// The item is removed and inserted again, unless `to` is this menu or submenu.
+    /// If inserting it fails, the item is put back where it was.
+    pub(crate) fn move_item_to(
+        &self,
+        item: &dyn IsMenuItem,
+        to: MenuParent,
+        position: usize,
+    ) -> crate::Result<()> {
+        if self.is_same(&to) {
+            return match self {
+                MenuParent::Menu(m) => m.move_item(item, position),
+                MenuParent::Submenu(s) => s.move_item(item, position),
+            };
+        }
+
+        if let MenuItemKind::Submenu(submenu) = item.kind() {
+            if to.is_within(&submenu) {
+                return Err(crate::Error::SubmenuIntoItself);
+            }
+        }
+
+        let index = self
+            .items()
+            .iter()
+            .position(|i| i.id() == item.id())
+            .ok_or(crate::Error::NotAChildOfThisMenu)?;
+        self.remove(item)?;
+        if let Err(e) = to.insert(item, position.min(to.items().len())) {
+            let _ = self.insert(item, index);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+}
+
+/// An owned [`MenuParent`].
+#[derive(Clone)]
+pub(crate) enum MenuParentKind {
+    Menu(Menu),
+    Submenu(Submenu),
+}
+
+impl MenuParentKind {
+    pub(crate) fn as_parent(&self) -> MenuParent<'_> {
+        match self {
+            MenuParentKind::Menu(m) => MenuParent::Menu(m),
+            MenuParentKind::Submenu(s) => MenuParent::Submenu(s),
+        }
+    }
+}
+
+/// A weak reference to the [`Menu`] or [`Submenu`] an item was added to,
+/// kept by the item, see [`MenuItem::parents`](crate::MenuItem::parents).
+#[derive(Clone)]
+pub(crate) enum WeakMenuParent {
+    Menu(WeakMenu),
+    Submenu(Rc<MenuId>, Weak<RefCell<MenuChild>>),
+}
+
+impl std::fmt::Debug for WeakMenuParent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WeakMenuParent").field(self.id()).finish()
+    }
+}
+
+impl WeakMenuParent {
+    pub(crate) fn id(&self) -> &MenuId {
+        match self {
+            WeakMenuParent::Menu(m) => m.id(),
+            WeakMenuParent::Submenu(id, _) => id,
+        }
+    }
+
+    pub(crate) fn upgrade(&self) -> Option<MenuParentKind> {
+        match self {
+            WeakMenuParent::Menu(m) => m.upgrade().map(MenuParentKind::Menu),
+            WeakMenuParent::Submenu(id, inner) => Some(MenuParentKind::Submenu(Submenu {
+                id: id.clone(),
+                inner: inner.upgrade()?,
+            })),
+        }
+    }
+
+    /// Whether this refers to `parent`.
+    pub(crate) fn is(&self, parent: &MenuParent) -> bool {
+        match (self, parent) {
+            (WeakMenuParent::Menu(a), MenuParent::Menu(b)) => a.ptr_eq(b),
+            (WeakMenuParent::Submenu(_, a), MenuParent::Submenu(b)) => {
+                std::ptr::eq(a.as_ptr(), Rc::as_ptr(&b.inner))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Records that `item` was added to `parent`.
+pub(crate) fn added_to(item: &dyn IsMenuItem, parent: MenuParent) {
+    item.kind().child_mut().parents.push(parent.downgrade());
+}
+
+/// Records that `item` was removed from `parent`, once.
+pub(crate) fn removed_from(item: &dyn IsMenuItem, parent: MenuParent) {
+    let kind = item.kind();
+    let mut child = kind.child_mut();
+    if let Some(i) = child.parents.iter().position(|p| p.is(&parent)) {
+        child.parents.remove(i);
+    }
+}
+
+/// Returns the ids of the menus and submenus `child` is in, without duplicates.
+pub(crate) fn parent_ids(child: &MenuChild) -> Vec<MenuId> {
+    let mut ids: Vec<MenuId> = Vec::new();
+    for parent in child.parents.iter().filter(|p| p.upgrade().is_some()) {
+        if !ids.contains(parent.id()) {
+            ids.push(parent.id().clone());
+        }
+    }
+    ids
+}
+
+/// Returns the menus `child` is in, directly or through submenus, without duplicates.
+pub(crate) fn root_menus(child: &MenuChild) -> Vec<Menu> {
+    let mut menus: Vec<Menu> = Vec::new();
+    let mut visited: Vec<Rc<RefCell<MenuChild>>> = Vec::new();
+    let mut stack = child.parents.clone();
+    while let Some(parent) = stack.pop() {
+        match parent.upgrade() {
+            Some(MenuParentKind::Menu(menu)) if !menus.iter().any(|m| m.ptr_eq(&menu)) => {
+                menus.push(menu);
+            }
+            // a submenu that is its own ancestor is only visited once
+            Some(MenuParentKind::Submenu(submenu))
+                if !visited.iter().any(|s| Rc::ptr_eq(s, &submenu.inner)) =>
+            {
+                stack.extend(submenu.inner.borrow().parents.iter().cloned());
+                visited.push(submenu.inner);
+            }
+            _ => {}
+        }
+    }
+    menus
+}
+
+/// Removes `item` from all the menus and submenus it is in.
+pub(crate) fn remove_from_all(item: &dyn IsMenuItem) {
+    let parents = item.kind().child().parents.clone();
+    for parent in parents.iter().filter_map(WeakMenuParent::upgrade) {
+        let _ = parent.as_parent().remove(item);
+    }
+    item.kind().child_mut().parents.clear();
+}
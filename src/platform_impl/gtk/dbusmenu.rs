@@ -0,0 +1,827 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use gtk::{gdk, gio, glib, glib::Variant, prelude::*};
+
+use super::{
+    accelerator::{parse_accelerator, to_gtk_mnemonic},
+    activate_child, register_observer, Menu, MenuChild, MenuObserver, MenuObservers,
+    PredefinedMenuItemType,
+};
+use crate::{util::Counter, MenuItemType};
+
+const INTERFACE_NAME: &str = "com.canonical.dbusmenu";
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="com.canonical.dbusmenu">
+    <property name="Version" type="u" access="read"/>
+    <property name="TextDirection" type="s" access="read"/>
+    <property name="Status" type="s" access="read"/>
+    <property name="IconThemePath" type="as" access="read"/>
+    <method name="GetLayout">
+      <arg type="i" name="parentId" direction="in"/>
+      <arg type="i" name="recursionDepth" direction="in"/>
+      <arg type="as" name="propertyNames" direction="in"/>
+      <arg type="u" name="revision" direction="out"/>
+      <arg type="(ia{sv}av)" name="layout" direction="out"/>
+    </method>
+    <method name="GetGroupProperties">
+      <arg type="ai" name="ids" direction="in"/>
+      <arg type="as" name="propertyNames" direction="in"/>
+      <arg type="a(ia{sv})" name="properties" direction="out"/>
+    </method>
+    <method name="GetProperty">
+      <arg type="i" name="id" direction="in"/>
+      <arg type="s" name="name" direction="in"/>
+      <arg type="v" name="value" direction="out"/>
+    </method>
+    <method name="Event">
+      <arg type="i" name="id" direction="in"/>
+      <arg type="s" name="eventId" direction="in"/>
+      <arg type="v" name="data" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+    <method name="EventGroup">
+      <arg type="a(isvu)" name="events" direction="in"/>
+      <arg type="ai" name="idErrors" direction="out"/>
+    </method>
+    <method name="AboutToShow">
+      <arg type="i" name="id" direction="in"/>
+      <arg type="b" name="needUpdate" direction="out"/>
+    </method>
+    <method name="AboutToShowGroup">
+      <arg type="ai" name="ids" direction="in"/>
+      <arg type="ai" name="updatesNeeded" direction="out"/>
+      <arg type="ai" name="idErrors" direction="out"/>
+    </method>
+    <signal name="ItemsPropertiesUpdated">
+      <arg type="a(ia{sv})" name="updatedProps" direction="out"/>
+      <arg type="a(ias)" name="removedProps" direction="out"/>
+    </signal>
+    <signal name="LayoutUpdated">
+      <arg type="u" name="revision" direction="out"/>
+      <arg type="i" name="parent" direction="out"/>
+    </signal>
+    <signal name="ItemActivationRequested">
+      <arg type="i" name="id" direction="out"/>
+      <arg type="u" name="timestamp" direction="out"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+static COUNTER: Counter = Counter::new();
+
+thread_local! {
+    /// Servers registered on this thread, the closures passed to
+    /// [`gio::DBusConnection::register_object`] must be `Send` and `Sync`
+    /// so they only hold a key into this map.
+    static SERVERS: RefCell<HashMap<u32, Weak<ServerState>>> = RefCell::new(HashMap::new());
+}
+
+type Properties = HashMap<String, Variant>;
+
+pub(crate) enum DBusMenuRoot {
+    Menu(Weak<RefCell<Menu>>),
+    Submenu(Weak<RefCell<MenuChild>>),
+}
+
+impl DBusMenuRoot {
+    fn children(&self) -> Option<Vec<Rc<RefCell<MenuChild>>>> {
+        match self {
+            DBusMenuRoot::Menu(menu) => menu.upgrade().map(|m| m.borrow().children.clone()),
+            DBusMenuRoot::Submenu(submenu) => {
+                submenu.upgrade().and_then(|s| s.borrow().children.clone())
+            }
+        }
+    }
+
+    fn observers(&self) -> Option<MenuObservers> {
+        match self {
+            DBusMenuRoot::Menu(menu) => menu.upgrade().map(|m| m.borrow().observers.clone()),
+            DBusMenuRoot::Submenu(submenu) => {
+                submenu.upgrade().map(|s| s.borrow().observers.clone())
+            }
+        }
+    }
+}
+
+pub(crate) struct DBusMenuServer {
+    state: Rc<ServerState>,
+}
+
+impl DBusMenuServer {
+    pub(crate) fn new(
+        root: DBusMenuRoot,
+        connection: &gio::DBusConnection,
+        object_path: &str,
+    ) -> crate::Result<Self> {
+        let interface = gio::DBusNodeInfo::for_xml(INTERFACE_XML)?
+            .lookup_interface(INTERFACE_NAME)
+            .unwrap();
+
+        let key = COUNTER.next();
+        let registration = connection.register_object(
+            object_path,
+            &interface,
+            move |_, _, _, _, method, parameters, invocation| {
+                handle_method_call(key, method, parameters, invocation)
+            },
+            |_, _, _, _, property| match property {
+                "Version" => 3u32.to_variant(),
+                "TextDirection" => "ltr".to_variant(),
+                "Status" => "normal".to_variant(),
+                _ => Vec::<String>::new().to_variant(),
+            },
+            |_, _, _, _, _, _| false,
+        )?;
+
+        let state = Rc::new_cyclic(|this| ServerState {
+            this: this.clone(),
+            key,
+            root,
+            connection: connection.clone(),
+            object_path: object_path.to_string(),
+            registration: RefCell::new(Some(registration)),
+            revision: Cell::new(0),
+            items: RefCell::new(Items::default()),
+            refresh_scheduled: Cell::new(false),
+        });
+
+        SERVERS.with(|servers| {
+            servers.borrow_mut().insert(key, Rc::downgrade(&state));
+        });
+        if let Some(observers) = state.root.observers() {
+            register_observer(&observers, state.clone());
+        }
+        state.refresh();
+
+        Ok(Self { state })
+    }
+
+    pub fn object_path(&self) -> &str {
+        &self.state.object_path
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.state.revision.get()
+    }
+}
+
+impl Drop for DBusMenuServer {
+    fn drop(&mut self) {
+        if let Some(registration) = self.state.registration.borrow_mut().take() {
+            let _ = self.state.connection.unregister_object(registration);
+        }
+        SERVERS.with(|servers| servers.borrow_mut().remove(&self.state.key));
+    }
+}
+
+#[derive(Default)]
+struct Items {
+    next_id: i32,
+    ids: HashMap<*const RefCell<MenuChild>, i32>,
+    children: HashMap<i32, Weak<RefCell<MenuChild>>>,
+    // the layout and properties last sent to clients
+    layout: HashMap<i32, Vec<i32>>,
+    properties: HashMap<i32, Properties>,
+}
+
+impl Items {
+    /// Returns the id of `child`, which stays the same as long as it is alive.
+    fn id(&mut self, child: &Rc<RefCell<MenuChild>>) -> i32 {
+        let ptr = Rc::as_ptr(child);
+        if let Some(id) = self.ids.get(&ptr) {
+            if self.children.get(id).is_some_and(|c| c.strong_count() > 0) {
+                return *id;
+            }
+        }
+
+        // 0 is the root
+        self.next_id += 1;
+        self.ids.insert(ptr, self.next_id);
+        self.children.insert(self.next_id, Rc::downgrade(child));
+        self.next_id
+    }
+}
+
+struct ServerState {
+    this: Weak<ServerState>,
+    key: u32,
+    root: DBusMenuRoot,
+    connection: gio::DBusConnection,
+    object_path: String,
+    registration: RefCell<Option<gio::RegistrationId>>,
+    revision: Cell<u32>,
+    items: RefCell<Items>,
+    refresh_scheduled: Cell<bool>,
+}
+
+impl MenuObserver for ServerState {
+    fn layout_changed(&self) {
+        if self.refresh_scheduled.replace(true) {
+            return;
+        }
+
+        let state = self.this.clone();
+        glib::idle_add_local_once(move || {
+            if let Some(state) = state.upgrade() {
+                state.refresh();
+            }
+        });
+    }
+}
+
+impl ServerState {
+    /// Compares the menu with what was last sent to clients and emits
+    /// `LayoutUpdated` if items were added or removed, `ItemsPropertiesUpdated` otherwise.
+    fn refresh(&self) {
+        self.refresh_scheduled.set(false);
+
+        let mut layout = HashMap::new();
+        let mut properties = HashMap::new();
+        properties.insert(0, root_properties());
+        {
+            let mut items = self.items.borrow_mut();
+            items.children.retain(|_, c| c.strong_count() > 0);
+            let Items { ids, children, .. } = &mut *items;
+            ids.retain(|_, id| children.contains_key(id));
+
+            let children = self.root.children().unwrap_or_default();
+            self.walk(0, &children, &mut items, &mut layout, &mut properties);
+        }
+
+        let mut items = self.items.borrow_mut();
+        if items.layout != layout {
+            items.layout = layout;
+            items.properties = properties;
+            drop(items);
+
+            self.revision.set(self.revision.get() + 1);
+            self.emit_signal("LayoutUpdated", &(self.revision.get(), 0i32).to_variant());
+            return;
+        }
+
+        let mut updated = Vec::new();
+        let mut removed = Vec::new();
+        for (id, props) in &properties {
+            let old = items.properties.get(id);
+            let changed = props
+                .iter()
+                .filter(|(k, v)| old.and_then(|old| old.get(*k)) != Some(v))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Properties>();
+            let gone = old
+                .map(|old| {
+                    old.keys()
+                        .filter(|k| !props.contains_key(*k))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            if !changed.is_empty() {
+                updated.push((*id, changed));
+            }
+            if !gone.is_empty() {
+                removed.push((*id, gone));
+            }
+        }
+        items.properties = properties;
+        drop(items);
+
+        if !updated.is_empty() || !removed.is_empty() {
+            self.emit_signal("ItemsPropertiesUpdated", &(updated, removed).to_variant());
+        }
+    }
+
+    fn walk(
+        &self,
+        parent: i32,
+        children: &[Rc<RefCell<MenuChild>>],
+        items: &mut Items,
+        layout: &mut HashMap<i32, Vec<i32>>,
+        properties: &mut HashMap<i32, Properties>,
+    ) {
+        let mut ids = Vec::new();
+        for child in children {
+            let child_ = child.borrow();
+            if !child_.is_supported() {
+                continue;
+            }
+
+            if let Some(this) = self.this.upgrade() {
+                register_observer(&child_.observers, this);
+            }
+
+            let id = items.id(child);
+            properties.insert(id, item_properties(&child_));
+            if let Some(children) = &child_.children {
+                self.walk(id, children, items, layout, properties);
+            }
+            ids.push(id);
+        }
+        layout.insert(parent, ids);
+    }
+
+    fn emit_signal(&self, name: &str, parameters: &Variant) {
+        let _ = self.connection.emit_signal(
+            None,
+            &self.object_path,
+            INTERFACE_NAME,
+            name,
+            Some(parameters),
+        );
+    }
+
+    fn layout(&self, items: &Items, id: i32, depth: i32, names: &[String]) -> Variant {
+        let children = if depth == 0 {
+            Vec::new()
+        } else {
+            items
+                .layout
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .map(|child| self.layout(items, *child, depth - 1, names))
+                .collect::<Vec<_>>()
+        };
+
+        Variant::tuple_from_iter([
+            id.to_variant(),
+            filter_properties(&items.properties[&id], names).to_variant(),
+            children.to_variant(),
+        ])
+    }
+
+    /// Handles an `Event` call, returns `false` if `id` isn't an item of this menu.
+    fn event(&self, id: i32, event_id: &str) -> bool {
+        let child = self
+            .items
+            .borrow()
+            .children
+            .get(&id)
+            .and_then(Weak::upgrade);
+        let Some(child) = child else {
+            return id == 0;
+        };
+
+        if event_id == "clicked" {
+            activate_child(&child);
+        }
+        true
+    }
+}
+
+fn handle_method_call(
+    key: u32,
+    method: &str,
+    parameters: Variant,
+    invocation: gio::DBusMethodInvocation,
+) {
+    let state = SERVERS.with(|servers| servers.borrow().get(&key).and_then(Weak::upgrade));
+    let Some(state) = state else {
+        invocation.return_dbus_error(
+            "org.freedesktop.DBus.Error.UnknownObject",
+            "This menu is no longer available",
+        );
+        return;
+    };
+
+    // answer with the current state of the menu, emitting any pending changes first
+    state.refresh();
+
+    let invalid_args = |invocation: gio::DBusMethodInvocation, message: &str| {
+        invocation.return_dbus_error("org.freedesktop.DBus.Error.InvalidArgs", message)
+    };
+
+    match method {
+        "GetLayout" => {
+            let (parent, depth, names) = parameters.get::<(i32, i32, Vec<String>)>().unwrap();
+            let items = state.items.borrow();
+            if !items.properties.contains_key(&parent) {
+                drop(items);
+                return invalid_args(invocation, "Unknown parent id");
+            }
+            let layout = state.layout(&items, parent, depth, &names);
+            drop(items);
+            invocation.return_value(Some(&Variant::tuple_from_iter([
+                state.revision.get().to_variant(),
+                layout,
+            ])));
+        }
+        "GetGroupProperties" => {
+            let (ids, names) = parameters.get::<(Vec<i32>, Vec<String>)>().unwrap();
+            let items = state.items.borrow();
+            let properties = ids
+                .into_iter()
+                .filter_map(|id| {
+                    let properties = items.properties.get(&id)?;
+                    Some((id, filter_properties(properties, &names)))
+                })
+                .collect::<Vec<_>>();
+            drop(items);
+            invocation.return_value(Some(&(properties,).to_variant()));
+        }
+        "GetProperty" => {
+            let (id, name) = parameters.get::<(i32, String)>().unwrap();
+            let value = state
+                .items
+                .borrow()
+                .properties
+                .get(&id)
+                .and_then(|p| p.get(&name).cloned());
+            match value {
+                Some(value) => invocation.return_value(Some(&(value,).to_variant())),
+                None => invalid_args(invocation, "Unknown id or property"),
+            }
+        }
+        "Event" => {
+            let (id, event_id, _, _) = parameters.get::<(i32, String, Variant, u32)>().unwrap();
+            // reply first, as activating an item may not return right away
+            if !state.items.borrow().properties.contains_key(&id) {
+                return invalid_args(invocation, "Unknown id");
+            }
+            invocation.return_value(None);
+            state.event(id, &event_id);
+        }
+        "EventGroup" => {
+            let events = parameters
+                .get::<(Vec<(i32, String, Variant, u32)>,)>()
+                .unwrap()
+                .0;
+            let (known, errors): (Vec<_>, Vec<_>) = {
+                let items = state.items.borrow();
+                events
+                    .into_iter()
+                    .partition(|(id, ..)| items.properties.contains_key(id))
+            };
+            let errors = errors.into_iter().map(|(id, ..)| id).collect::<Vec<_>>();
+            if known.is_empty() && !errors.is_empty() {
+                return invalid_args(invocation, "Unknown ids");
+            }
+            invocation.return_value(Some(&(errors,).to_variant()));
+            for (id, event_id, ..) in known {
+                state.event(id, &event_id);
+            }
+        }
+        "AboutToShow" => {
+            // the layout is always up to date
+            invocation.return_value(Some(&(false,).to_variant()));
+        }
+        "AboutToShowGroup" => {
+            let ids = parameters.get::<(Vec<i32>,)>().unwrap().0;
+            let items = state.items.borrow();
+            let errors = ids
+                .into_iter()
+                .filter(|id| !items.properties.contains_key(id))
+                .collect::<Vec<_>>();
+            drop(items);
+            invocation.return_value(Some(&(Vec::<i32>::new(), errors).to_variant()));
+        }
+        _ => invocation.return_dbus_error(
+            "org.freedesktop.DBus.Error.UnknownMethod",
+            &format!("Unknown method {method}"),
+        ),
+    }
+}
+
+fn root_properties() -> Properties {
+    let mut properties = Properties::new();
+    properties.insert("children-display".into(), "submenu".to_variant());
+    properties
+}
+
+fn item_properties(child: &MenuChild) -> Properties {
+    let mut properties = Properties::new();
+
+    if matches!(
+        child.predefined_item_type,
+        Some(PredefinedMenuItemType::Separator)
+    ) {
+        properties.insert("type".into(), "separator".to_variant());
+        return properties;
+    }
+
+    properties.insert("label".into(), to_gtk_mnemonic(&child.text).to_variant());
+    properties.insert("enabled".into(), child.is_enabled().to_variant());
+
+    match child.item_type {
+        MenuItemType::Submenu => {
+            properties.insert("children-display".into(), "submenu".to_variant());
+        }
+        MenuItemType::Check => {
+            properties.insert("toggle-type".into(), "checkmark".to_variant());
+            properties.insert(
+                "toggle-state".into(),
+                (child.is_checked() as i32).to_variant(),
+            );
+        }
+        _ => {}
+    }
+
+    if let Some(png) = child
+        .icon
+        .as_ref()
+        .and_then(|icon| icon.inner.to_pixbuf().save_to_bufferv("png", &[]).ok())
+    {
+        properties.insert("icon-data".into(), png.to_variant());
+    }
+
+    if let Some(shortcut) = child
+        .accelerator
+        .as_ref()
+        .and_then(|accelerator| parse_accelerator(accelerator).ok())
+        .and_then(|(mods, key)| shortcut(mods, key))
+    {
+        properties.insert("shortcut".into(), vec![shortcut].to_variant());
+    }
+
+    properties
+}
+
+/// Converts an accelerator into the key names dbusmenu clients expect, e.g. `["Control", "c"]`.
+fn shortcut(mods: gdk::ModifierType, key: u32) -> Option<Vec<String>> {
+    let mut shortcut = [
+        (gdk::ModifierType::CONTROL_MASK, "Control"),
+        (gdk::ModifierType::MOD1_MASK, "Alt"),
+        (gdk::ModifierType::SHIFT_MASK, "Shift"),
+        (gdk::ModifierType::META_MASK, "Super"),
+    ]
+    .into_iter()
+    .filter(|(mask, _)| mods.contains(*mask))
+    .map(|(_, name)| name.to_string())
+    .collect::<Vec<_>>();
+
+    let key = gdk::keys::Key::from(key).to_lower().name()?;
+    shortcut.push(key.to_string());
+    Some(shortcut)
+}
+
+/// Returns the `names` properties, or all of them if `names` is empty.
+fn filter_properties(properties: &Properties, names: &[String]) -> Properties {
+    if names.is_empty() {
+        return properties.clone();
+    }
+
+    properties
+        .iter()
+        .filter(|(k, _)| names.contains(k))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{CheckMenuItem, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+
+    struct Bus(Child);
+
+    impl Bus {
+        fn spawn() -> Option<(Self, String)> {
+            let mut child = Command::new("dbus-daemon")
+                .args(["--session", "--print-address", "--nofork"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(child.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some((Self(child), address.trim().to_string()))
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    fn connect(address: &str) -> gio::DBusConnection {
+        gio::DBusConnection::for_address_sync(
+            address,
+            gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+            None,
+            None::<&gio::Cancellable>,
+        )
+        .unwrap()
+    }
+
+    /// Calls `method` on the menu, iterating `context` until the reply arrives.
+    fn call(
+        context: &glib::MainContext,
+        client: &gio::DBusConnection,
+        server: &gio::DBusConnection,
+        method: &str,
+        parameters: Variant,
+    ) -> Result<Variant, glib::Error> {
+        let reply = Rc::new(RefCell::new(None));
+        let reply_ = reply.clone();
+        client.call(
+            server.unique_name().as_deref(),
+            "/MenuBar",
+            INTERFACE_NAME,
+            method,
+            Some(&parameters),
+            None,
+            gio::DBusCallFlags::NONE,
+            5000,
+            None::<&gio::Cancellable>,
+            move |result| *reply_.borrow_mut() = Some(result),
+        );
+        let start = std::time::Instant::now();
+        while reply.borrow().is_none() && start.elapsed() < Duration::from_secs(5) {
+            context.iteration(true);
+        }
+        let reply = reply.borrow_mut().take().unwrap();
+        reply
+    }
+
+    #[test]
+    fn menu_is_served_over_dbus() {
+        let Some((_bus, address)) = Bus::spawn() else {
+            // no dbus-daemon to run against
+            return;
+        };
+
+        let context = glib::MainContext::new();
+        context
+            .with_thread_default(|| {
+                let server = connect(&address);
+                let client = connect(&address);
+
+                let (tx, signals) = std::sync::mpsc::channel();
+                client.signal_subscribe(
+                    None,
+                    Some(INTERFACE_NAME),
+                    None,
+                    Some("/MenuBar"),
+                    None,
+                    gio::DBusSignalFlags::NONE,
+                    move |_, _, _, _, signal, _| {
+                        let _ = tx.send(signal.to_string());
+                    },
+                );
+
+                let item = MenuItem::with_id("item", "&Item", true, None);
+                let check = CheckMenuItem::with_id("check", "Check", true, false, None);
+                let submenu = Submenu::with_id_and_items(
+                    "submenu",
+                    "File",
+                    true,
+                    &[&item, &PredefinedMenuItem::separator(), &check],
+                )
+                .unwrap();
+                let menu = crate::Menu::with_items(&[&submenu]).unwrap();
+                let served = menu
+                    .serve_dbusmenu_on_connection(&server, "/MenuBar")
+                    .unwrap();
+                assert_eq!(served.revision(), 1);
+
+                // the whole tree
+                let reply = call(
+                    &context,
+                    &client,
+                    &server,
+                    "GetLayout",
+                    (0i32, -1i32, Vec::<String>::new()).to_variant(),
+                )
+                .unwrap();
+                let (revision, (root, _, children)) = reply
+                    .get::<(u32, (i32, HashMap<String, Variant>, Vec<Variant>))>()
+                    .unwrap();
+                assert_eq!((revision, root), (1, 0));
+                assert_eq!(children.len(), 1);
+                let (file, props, items) = children[0]
+                    .as_variant()
+                    .unwrap()
+                    .get::<(i32, HashMap<String, Variant>, Vec<Variant>)>()
+                    .unwrap();
+                assert_eq!(props["label"].get::<String>().as_deref(), Some("File"));
+                assert_eq!(items.len(), 3);
+
+                let (item_id, props, _) = items[0]
+                    .as_variant()
+                    .unwrap()
+                    .get::<(i32, HashMap<String, Variant>, Vec<Variant>)>()
+                    .unwrap();
+                assert_eq!(props["label"].get::<String>().as_deref(), Some("_Item"));
+                let (_, props, _) = items[1]
+                    .as_variant()
+                    .unwrap()
+                    .get::<(i32, HashMap<String, Variant>, Vec<Variant>)>()
+                    .unwrap();
+                assert_eq!(props["type"].get::<String>().as_deref(), Some("separator"));
+                let (check_id, props, _) = items[2]
+                    .as_variant()
+                    .unwrap()
+                    .get::<(i32, HashMap<String, Variant>, Vec<Variant>)>()
+                    .unwrap();
+                assert_eq!(props["toggle-state"].get::<i32>(), Some(0));
+
+                // a limited depth only returns the direct children
+                let reply = call(
+                    &context,
+                    &client,
+                    &server,
+                    "GetLayout",
+                    (file, 0i32, vec!["label".to_string()]).to_variant(),
+                )
+                .unwrap();
+                let (_, (_, props, children)) = reply
+                    .get::<(u32, (i32, HashMap<String, Variant>, Vec<Variant>))>()
+                    .unwrap();
+                assert!(children.is_empty());
+                assert_eq!(props.len(), 1);
+
+                // property changes don't change the layout
+                item.set_enabled(false);
+                let reply = call(
+                    &context,
+                    &client,
+                    &server,
+                    "GetProperty",
+                    (item_id, "enabled").to_variant(),
+                )
+                .unwrap();
+                assert_eq!(
+                    reply.child_value(0).as_variant().unwrap().get(),
+                    Some(false)
+                );
+                assert_eq!(served.revision(), 1);
+
+                // clicking a check item toggles it and emits an event
+                call(
+                    &context,
+                    &client,
+                    &server,
+                    "Event",
+                    (check_id, "clicked", 0i32.to_variant(), 0u32).to_variant(),
+                )
+                .unwrap();
+                assert!(check.is_checked());
+                assert!(MenuEvent::receiver()
+                    .try_iter()
+                    .any(|e| e.id() == check.id()));
+
+                // unknown ids are errors
+                assert!(call(
+                    &context,
+                    &client,
+                    &server,
+                    "Event",
+                    (1000i32, "clicked", 0i32.to_variant(), 0u32).to_variant(),
+                )
+                .is_err());
+
+                // adding items bumps the revision
+                submenu
+                    .append(&MenuItem::with_id("new", "New", true, None))
+                    .unwrap();
+                call(
+                    &context,
+                    &client,
+                    &server,
+                    "AboutToShow",
+                    (file,).to_variant(),
+                )
+                .unwrap();
+                assert_eq!(served.revision(), 2);
+
+                while context.iteration(false) {}
+                let signals = signals.try_iter().collect::<Vec<_>>();
+                assert!(signals.iter().any(|s| s == "ItemsPropertiesUpdated"));
+                assert!(signals.iter().any(|s| s == "LayoutUpdated"));
+
+                // dropping the server unpublishes the menu
+                drop(served);
+                assert!(call(
+                    &context,
+                    &client,
+                    &server,
+                    "GetLayout",
+                    (0i32, -1i32, Vec::<String>::new()).to_variant(),
+                )
+                .is_err());
+            })
+            .unwrap();
+    }
+}
@@ -2,30 +2,30 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    io,
+    sync::{Arc, Weak},
+};
+
 use gtk::gdk_pixbuf::{Colorspace, Pixbuf};
 
-use crate::icon::BadIcon;
+use crate::icon::{BadIcon, IconCacheStats, RgbaIcon};
 
 /// An icon used for the window titlebar, taskbar, etc.
 #[derive(Debug, Clone)]
 pub struct PlatformIcon {
-    raw: Vec<u8>,
-    width: i32,
-    height: i32,
+    rgba: Arc<RgbaIcon>,
     row_stride: i32,
+    // hash of `rgba`, computed once and used as the key in `PIXBUF_CACHE`
+    content_hash: u64,
 }
 
 impl From<PlatformIcon> for Pixbuf {
     fn from(icon: PlatformIcon) -> Self {
-        Pixbuf::from_mut_slice(
-            icon.raw,
-            gtk::gdk_pixbuf::Colorspace::Rgb,
-            true,
-            8,
-            icon.width,
-            icon.height,
-            icon.row_stride,
-        )
+        icon.to_pixbuf()
     }
 }
 
@@ -35,31 +35,217 @@ impl PlatformIcon {
     /// The length of `rgba` must be divisible by 4, and `width * height` must equal
     /// `rgba.len() / 4`. Otherwise, this will return a `BadIcon` error.
     pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        let rgba = RgbaIcon::from_rgba(rgba, width, height)?;
         let row_stride =
             Pixbuf::calculate_rowstride(Colorspace::Rgb, true, 8, width as i32, height as i32);
+        let mut hasher = DefaultHasher::new();
+        rgba.hash(&mut hasher);
         Ok(Self {
-            raw: rgba,
-            width: width as i32,
-            height: height as i32,
+            rgba: Arc::new(rgba),
             row_stride,
+            content_hash: hasher.finish(),
         })
     }
 
+    pub(crate) fn rgba_icon(&self) -> &RgbaIcon {
+        &self.rgba
+    }
+
+    pub(crate) fn into_rgba_icon(self) -> RgbaIcon {
+        Arc::try_unwrap(self.rgba).unwrap_or_else(|rgba| (*rgba).clone())
+    }
+
+    /// Returns the pixel data shared by the clones of this icon and its hash,
+    /// used by the texture cache of the GTK4 menus.
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn shared_rgba(&self) -> (&Arc<RgbaIcon>, u64) {
+        (&self.rgba, self.content_hash)
+    }
+
     pub fn to_pixbuf(&self) -> Pixbuf {
-        Pixbuf::from_mut_slice(
-            self.raw.clone(),
-            gtk::gdk_pixbuf::Colorspace::Rgb,
-            true,
-            8,
-            self.width,
-            self.height,
-            self.row_stride,
-        )
+        pixbuf_from_rgba_icon(&self.rgba, self.row_stride)
+    }
+
+    pub fn to_pixbuf_scale(&self, w: i32, h: i32) -> Result<Pixbuf, BadIcon> {
+        scale_pixbuf(&self.to_pixbuf(), w, h)
+    }
+
+    /// Same as [`PlatformIcon::to_pixbuf_scale`] but the pixbuf is shared with
+    /// any other icon that has the same content, see [`PIXBUF_CACHE`].
+    ///
+    /// If `grayscale` is `true`, the pixbuf is created from a grayscale variant of this icon.
+    pub fn to_cached_pixbuf_scale(
+        &self,
+        w: i32,
+        h: i32,
+        grayscale: bool,
+    ) -> Result<Pixbuf, BadIcon> {
+        let key = CacheKey {
+            content_hash: self.content_hash,
+            width: w,
+            height: h,
+            grayscale,
+        };
+
+        PIXBUF_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(pixbuf) = cache.get(&key, &self.rgba) {
+                return Ok(pixbuf);
+            }
+
+            let pixbuf = if grayscale {
+                scale_pixbuf(
+                    &pixbuf_from_rgba_icon(&self.rgba.to_grayscale(), self.row_stride),
+                    w,
+                    h,
+                )?
+            } else {
+                self.to_pixbuf_scale(w, h)?
+            };
+            cache.insert(key, &self.rgba, pixbuf.clone());
+            Ok(pixbuf)
+        })
+    }
+}
+
+fn pixbuf_from_rgba_icon(icon: &RgbaIcon, row_stride: i32) -> Pixbuf {
+    Pixbuf::from_mut_slice(
+        icon.rgba.clone(),
+        gtk::gdk_pixbuf::Colorspace::Rgb,
+        true,
+        8,
+        icon.width as i32,
+        icon.height as i32,
+        row_stride,
+    )
+}
+
+fn scale_pixbuf(pixbuf: &Pixbuf, w: i32, h: i32) -> Result<Pixbuf, BadIcon> {
+    if w <= 0 || h <= 0 {
+        return Err(BadIcon::ZeroSize {
+            width: w.max(0) as u32,
+            height: h.max(0) as u32,
+        });
+    }
+
+    pixbuf
+        .scale_simple(w, h, gtk::gdk_pixbuf::InterpType::Bilinear)
+        .ok_or_else(|| {
+            BadIcon::OsError(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to allocate the scaled pixbuf",
+            ))
+        })
+}
+
+thread_local! {
+    /// Pixbufs created for icon menu items, shared by every gtk menu item
+    /// (in all menubars and context menus) that displays an icon with the same content.
+    ///
+    /// Entries only hold weak references to the icon data they were used for
+    /// and are evicted once every icon with their content is dropped.
+    static PIXBUF_CACHE: RefCell<PixbufCache> = RefCell::new(PixbufCache::default());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    content_hash: u64,
+    width: i32,
+    height: i32,
+    grayscale: bool,
+}
+
+struct CacheEntry {
+    // every distinct icon data with this content that hit the entry
+    icons: Vec<Weak<RgbaIcon>>,
+    pixbuf: Pixbuf,
+}
+
+impl CacheEntry {
+    /// Whether `icon` has the content of this entry, in which case it
+    /// also keeps the entry alive from now on.
+    fn matches(&mut self, icon: &Arc<RgbaIcon>) -> bool {
+        if self
+            .icons
+            .iter()
+            .any(|cached| cached.as_ptr() == Arc::as_ptr(icon))
+        {
+            return true;
+        }
+
+        self.icons.retain(|cached| cached.strong_count() > 0);
+        let matches = self
+            .icons
+            .iter()
+            .find_map(Weak::upgrade)
+            .is_some_and(|cached| *cached == **icon);
+        if matches {
+            self.icons.push(Arc::downgrade(icon));
+        }
+        matches
     }
 
-    pub fn to_pixbuf_scale(&self, w: i32, h: i32) -> Pixbuf {
-        self.to_pixbuf()
-            .scale_simple(w, h, gtk::gdk_pixbuf::InterpType::Bilinear)
-            .unwrap()
+    fn is_alive(&self) -> bool {
+        self.icons.iter().any(|icon| icon.strong_count() > 0)
     }
 }
+
+#[derive(Default)]
+struct PixbufCache {
+    // a bucket may hold more than one entry in case of hash collisions
+    entries: HashMap<CacheKey, Vec<CacheEntry>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PixbufCache {
+    fn get(&mut self, key: &CacheKey, icon: &Arc<RgbaIcon>) -> Option<Pixbuf> {
+        let pixbuf = self.entries.get_mut(key).and_then(|bucket| {
+            bucket
+                .iter_mut()
+                .find_map(|entry| entry.matches(icon).then(|| entry.pixbuf.clone()))
+        });
+
+        if pixbuf.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        pixbuf
+    }
+
+    fn insert(&mut self, key: CacheKey, icon: &Arc<RgbaIcon>, pixbuf: Pixbuf) {
+        self.evict_dropped();
+        self.entries.entry(key).or_default().push(CacheEntry {
+            icons: vec![Arc::downgrade(icon)],
+            pixbuf,
+        });
+    }
+
+    fn evict_dropped(&mut self) {
+        self.entries.retain(|_, bucket| {
+            bucket.retain(CacheEntry::is_alive);
+            !bucket.is_empty()
+        });
+    }
+
+    fn stats(&mut self) -> IconCacheStats {
+        self.evict_dropped();
+        let pixbufs = self.entries.values().flatten().map(|e| &e.pixbuf);
+        IconCacheStats {
+            entries: pixbufs.clone().count(),
+            bytes: pixbufs.map(|p| p.byte_length()).sum(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+pub(crate) fn icon_cache_stats() -> IconCacheStats {
+    PIXBUF_CACHE.with(|cache| cache.borrow_mut().stats())
+}
+
+pub(crate) fn clear_icon_cache() {
+    PIXBUF_CACHE.with(|cache| *cache.borrow_mut() = PixbufCache::default());
+}
@@ -0,0 +1,337 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{self, Write},
+    rc::{Rc, Weak},
+};
+
+use gtk::{gio, glib, prelude::*};
+
+use super::{
+    accelerator::{parse_accelerator, to_gtk_mnemonic},
+    activate_child, register_observer, Menu, MenuChild, MenuObserver, PredefinedMenuItemType,
+};
+use crate::{MenuId, MenuItemType};
+
+/// The name the action group returned by [`Menu::to_gio_menu_model`]
+/// is expected to be inserted with.
+const ACTION_GROUP_NAME: &str = "muda";
+
+/// A [`gio::Menu`] and a [`gio::SimpleActionGroup`] mirroring a [`Menu`].
+///
+/// Every item of the exported menu keeps a weak reference to the export,
+/// changes to the structure, text, icon or accelerator of an item rebuild the model
+/// on the next idle, while enabled and checked states are applied to the actions directly.
+pub(crate) struct MenuModelExport {
+    this: Weak<MenuModelExport>,
+    root: Weak<RefCell<Menu>>,
+    pub(crate) model: gio::Menu,
+    pub(crate) actions: gio::SimpleActionGroup,
+    rebuild_scheduled: Cell<bool>,
+}
+
+impl fmt::Debug for MenuModelExport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MenuModelExport")
+            .field("model", &self.model)
+            .field("actions", &self.actions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MenuModelExport {
+    pub(crate) fn new(root: &Rc<RefCell<Menu>>) -> Rc<Self> {
+        let export = Rc::new_cyclic(|this| Self {
+            this: this.clone(),
+            root: Rc::downgrade(root),
+            model: gio::Menu::new(),
+            actions: gio::SimpleActionGroup::new(),
+            rebuild_scheduled: Cell::new(false),
+        });
+        export.rebuild();
+        export
+    }
+
+    fn schedule_rebuild(&self) {
+        if self.rebuild_scheduled.replace(true) {
+            return;
+        }
+
+        let export = self.this.clone();
+        glib::idle_add_local_once(move || {
+            if let Some(export) = export.upgrade() {
+                export.rebuild();
+            }
+        });
+    }
+
+    fn rebuild(self: &Rc<Self>) {
+        self.rebuild_scheduled.set(false);
+
+        let Some(root) = self.root.upgrade() else {
+            return;
+        };
+
+        let app = application();
+        for name in self.actions.list_actions() {
+            if let Some(app) = &app {
+                app.set_accels_for_action(&format!("{ACTION_GROUP_NAME}.{name}"), &[]);
+            }
+            self.actions.remove_action(&name);
+        }
+
+        self.model.remove_all();
+        let children = root.borrow().children.clone();
+        self.append_items(&self.model, &children, app.as_ref());
+    }
+
+    /// Appends `children` to `menu`, separators split the items into sections.
+    fn append_items(
+        self: &Rc<Self>,
+        menu: &gio::Menu,
+        children: &[Rc<RefCell<MenuChild>>],
+        app: Option<&gtk::Application>,
+    ) {
+        let mut section = gio::Menu::new();
+        for child in children {
+            let child_ = child.borrow();
+            if !child_.is_supported() {
+                continue;
+            }
+
+            register_observer(&child_.observers, self.clone());
+
+            if matches!(
+                child_.predefined_item_type,
+                Some(PredefinedMenuItemType::Separator)
+            ) {
+                if section.n_items() > 0 {
+                    menu.append_section(None, &section);
+                    section = gio::Menu::new();
+                }
+                continue;
+            }
+
+            let item = if child_.item_type == MenuItemType::Submenu {
+                let submenu = gio::Menu::new();
+                self.append_items(&submenu, child_.children.as_ref().unwrap(), app);
+                let item =
+                    gio::MenuItem::new_submenu(Some(&to_gtk_mnemonic(&child_.text)), &submenu);
+
+                // submenus can only be disabled through an action
+                let name = action_name(&child_.id);
+                let action = gio::SimpleAction::new_stateful(&name, None, &false.to_variant());
+                action.set_enabled(child_.enabled);
+                self.actions.add_action(&action);
+                item.set_attribute_value(
+                    "submenu-action",
+                    Some(&format!("{ACTION_GROUP_NAME}.{name}").to_variant()),
+                );
+
+                item
+            } else {
+                self.make_item(child, &child_, app)
+            };
+            section.append_item(&item);
+        }
+
+        if section.n_items() > 0 {
+            menu.append_section(None, &section);
+        }
+    }
+
+    fn make_item(
+        &self,
+        child: &Rc<RefCell<MenuChild>>,
+        child_: &MenuChild,
+        app: Option<&gtk::Application>,
+    ) -> gio::MenuItem {
+        let name = action_name(&child_.id);
+        let detailed_name = format!("{ACTION_GROUP_NAME}.{name}");
+        let item = gio::MenuItem::new(Some(&to_gtk_mnemonic(&child_.text)), Some(&detailed_name));
+
+        let action = if child_.item_type == MenuItemType::Check {
+            gio::SimpleAction::new_stateful(&name, None, &child_.is_checked().to_variant())
+        } else {
+            gio::SimpleAction::new(&name, None)
+        };
+        action.set_enabled(child_.is_enabled());
+
+        if let Some(pixbuf) = child_
+            .icon
+            .as_ref()
+            .and_then(|icon| icon.inner.to_cached_pixbuf_scale(16, 16, false).ok())
+        {
+            item.set_icon(&pixbuf);
+        }
+
+        let accel = child_
+            .accelerator
+            .as_ref()
+            .and_then(|accelerator| parse_accelerator(accelerator).ok())
+            .and_then(|(mods, key)| gtk::accelerator_name(key, mods));
+        if let Some(accel) = accel {
+            if matches!(
+                child_.predefined_item_type,
+                Some(
+                    PredefinedMenuItemType::Copy
+                        | PredefinedMenuItemType::Cut
+                        | PredefinedMenuItemType::Paste
+                        | PredefinedMenuItemType::SelectAll
+                        | PredefinedMenuItemType::Undo
+                        | PredefinedMenuItemType::Redo
+                )
+            ) {
+                // only displayed, so the keys still reach the focused widget
+                item.set_attribute_value("accel", Some(&accel.to_variant()));
+            } else if let Some(app) = app {
+                app.set_accels_for_action(&detailed_name, &[&accel]);
+            }
+        }
+
+        let child = Rc::downgrade(child);
+        action.connect_activate(move |_, _| {
+            if let Some(child) = child.upgrade() {
+                activate_child(&child);
+            }
+        });
+        self.actions.add_action(&action);
+
+        item
+    }
+
+    fn action(&self, id: &MenuId) -> Option<gio::SimpleAction> {
+        self.actions
+            .lookup_action(&action_name(id))
+            .and_then(|a| a.downcast().ok())
+    }
+}
+
+impl MenuObserver for MenuModelExport {
+    fn layout_changed(&self) {
+        self.schedule_rebuild();
+    }
+
+    fn enabled_changed(&self, id: &MenuId, enabled: bool) {
+        if let Some(action) = self.action(id) {
+            action.set_enabled(enabled);
+        }
+    }
+
+    fn checked_changed(&self, id: &MenuId, checked: bool) {
+        if let Some(action) = self.action(id) {
+            action.set_state(&checked.to_variant());
+        }
+    }
+}
+
+fn application() -> Option<gtk::Application> {
+    gio::Application::default().and_then(|app| app.downcast().ok())
+}
+
+/// Escapes `id` into a valid action name, which can only contain
+/// ASCII alphanumerics, `-` and `.`.
+fn action_name(id: &MenuId) -> String {
+    let mut name = String::with_capacity(id.0.len());
+    for b in id.0.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'-' {
+            name.push(b as char);
+        } else {
+            let _ = write!(name, ".{b:02x}");
+        }
+    }
+
+    if name.is_empty() {
+        name.push('.');
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::with_gtk, *};
+    use crate::{CheckMenuItem, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+
+    fn flush_events() {
+        while glib::MainContext::default().iteration(false) {}
+    }
+
+    fn label(model: &impl IsA<gio::MenuModel>, index: i32) -> Option<String> {
+        model
+            .item_attribute_value(index, "label", None)
+            .and_then(|v| v.get::<String>())
+    }
+
+    #[test]
+    fn menu_model_stays_in_sync() {
+        with_gtk(|| {
+            let item = MenuItem::with_id("item", "&Item", true, None);
+            let check = CheckMenuItem::with_id("check", "Check", true, false, None);
+            let submenu = Submenu::with_id_and_items(
+                "submenu",
+                "File",
+                true,
+                &[&item, &PredefinedMenuItem::separator(), &check],
+            )
+            .unwrap();
+            let menu = crate::Menu::with_items(&[&submenu]).unwrap();
+
+            let (model, actions) = menu.to_gio_menu_model();
+            let (same_model, _) = menu.to_gio_menu_model();
+            assert_eq!(model, same_model);
+
+            // a single section holding the submenu, which has two sections
+            assert_eq!(model.n_items(), 1);
+            let section = model.item_link(0, "section").unwrap();
+            assert_eq!(label(&section, 0).as_deref(), Some("File"));
+            let file = section.item_link(0, "submenu").unwrap();
+            assert_eq!(file.n_items(), 2);
+            let first = file.item_link(0, "section").unwrap();
+            assert_eq!(label(&first, 0).as_deref(), Some("_Item"));
+
+            // enabled and checked states are applied right away
+            item.set_enabled(false);
+            assert!(!actions.is_action_enabled("item"));
+            check.set_checked(true);
+            assert_eq!(actions.action_state("check"), Some(true.to_variant()));
+
+            // activating a check action toggles the item and emits an event
+            item.set_enabled(true);
+            actions.activate_action("check", None);
+            assert!(!check.is_checked());
+            assert!(MenuEvent::receiver()
+                .try_iter()
+                .any(|e| e.id() == check.id()));
+
+            // text and structure changes rebuild the model on idle
+            item.set_text("Renamed");
+            submenu
+                .append(&MenuItem::with_id("new", "New", true, None))
+                .unwrap();
+            flush_events();
+            let section = model.item_link(0, "section").unwrap();
+            let file = section.item_link(0, "submenu").unwrap();
+            let first = file.item_link(0, "section").unwrap();
+            assert_eq!(label(&first, 0).as_deref(), Some("Renamed"));
+            let second = file.item_link(1, "section").unwrap();
+            assert_eq!(second.n_items(), 2);
+            assert!(actions.has_action("new"));
+        });
+    }
+
+    #[test]
+    fn action_names_are_valid() {
+        for id in ["", "quit", "file.open", "Ünïcode id", "a-b_c::d(e)"] {
+            let name = action_name(&MenuId::new(id));
+            assert!(gio::Action::name_is_valid(&name), "{name:?}");
+        }
+        assert_ne!(
+            action_name(&MenuId::new("a.b")),
+            action_name(&MenuId::new("a_b"))
+        );
+    }
+}
@@ -3,26 +3,38 @@
 // SPDX-License-Identifier: MIT
 
 mod accelerator;
+#[cfg(feature = "dbusmenu")]
+mod dbusmenu;
 mod icon;
+mod menu_model;
 
-pub(crate) use icon::PlatformIcon;
+#[cfg(feature = "dbusmenu")]
+pub(crate) use dbusmenu::DBusMenuRoot;
+#[cfg(feature = "dbusmenu")]
+pub(crate) use dbusmenu::DBusMenuServer;
+pub(crate) use icon::{clear_icon_cache, icon_cache_stats, PlatformIcon};
 
+use super::observer::{notify_observers, register_observer, MenuObserver, MenuObservers};
 use crate::{
     accelerator::Accelerator,
-    dpi::Position,
     icon::{Icon, NativeIcon},
     items::*,
     util::{AddOp, Counter},
-    IsMenuItem, MenuEvent, MenuId, MenuItemKind, MenuItemType,
+    AboutMetadata, ContextMenuAnchor, ContextMenuAnchorHints, ContextMenuGravity,
+    ContextMenuOptions, IsMenuItem, LicenseKind, MenuEvent, MenuHighlightEvent, MenuId,
+    MenuItemKind, MenuItemType,
 };
 use accelerator::{from_gtk_mnemonic, parse_accelerator, to_gtk_mnemonic};
 use glib::translate::ToGlibPtr;
-use gtk::{gdk, glib, prelude::*, AboutDialog, Container, Orientation};
+use gtk::{gdk, gio, glib, prelude::*, AboutDialog, Container, Orientation};
+use menu_model::MenuModelExport;
 use std::{
-    cell::RefCell,
+    any::Any,
+    cell::{Cell, RefCell},
     collections::{hash_map::Entry, HashMap},
     rc::Rc,
     sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
 static COUNTER: Counter = Counter::new();
@@ -30,21 +42,7 @@ static COUNTER: Counter = Counter::new();
 macro_rules! is_item_supported {
     ($item:tt) => {{
         let child = $item.child();
-        let child_ = child.borrow();
-        let supported = if let Some(predefined_item_type) = &child_.predefined_item_type {
-            matches!(
-                predefined_item_type,
-                PredefinedMenuItemType::Separator
-                    | PredefinedMenuItemType::Copy
-                    | PredefinedMenuItemType::Cut
-                    | PredefinedMenuItemType::Paste
-                    | PredefinedMenuItemType::SelectAll
-                    | PredefinedMenuItemType::About(_)
-            )
-        } else {
-            true
-        };
-        drop(child_);
+        let supported = child.borrow().is_supported();
         supported
     }};
 }
@@ -57,17 +55,42 @@ macro_rules! return_if_item_not_supported {
     };
 }
 
+/// Whether the gtk items of the items added to a menu are created right away,
+/// see [`crate::Menu::batch`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Batch {
+    #[default]
+    Off,
+    Started,
+    // items were added since the batch started
+    Pending,
+}
+
 pub struct Menu {
     id: MenuId,
     children: Vec<Rc<RefCell<MenuChild>>>,
-    // TODO: maybe save a reference to the window?
+    // keyed by `window_id`
     gtk_menubars: HashMap<u32, gtk::MenuBar>,
+    // the `destroy` handlers connected to the windows in `gtk_menubars`
+    gtk_windows: HashMap<u32, (glib::WeakRef<gtk::Window>, glib::SignalHandlerId)>,
     accel_group: Option<gtk::AccelGroup>,
     gtk_menu: (u32, Option<gtk::Menu>), // dedicated menu for tray or context menus
+    observers: MenuObservers,
+    gio_export: Option<Rc<MenuModelExport>>,
+    batch: Batch,
+    // the GTK4 menubars and context menu of this menu
+    #[cfg(feature = "gtk4")]
+    pub(crate) gtk4: super::gtk4::Gtk4Menu,
 }
 
 impl Drop for Menu {
     fn drop(&mut self) {
+        for (window, handler) in self.gtk_windows.drain().map(|(_, w)| w) {
+            if let Some(window) = window.upgrade() {
+                window.disconnect(handler);
+            }
+        }
+
         for (id, menu) in &self.gtk_menubars {
             drop_children_from_menu_and_destroy(*id, menu, &self.children);
             unsafe { menu.destroy() }
@@ -83,11 +106,17 @@ impl Drop for Menu {
 impl Menu {
     pub fn new(id: Option<MenuId>) -> Self {
         Self {
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             children: Vec::new(),
             gtk_menubars: HashMap::new(),
+            gtk_windows: HashMap::new(),
             accel_group: None,
             gtk_menu: (COUNTER.next(), None),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
+            gio_export: None,
         }
     }
 
@@ -96,7 +125,9 @@ impl Menu {
     }
 
     pub fn add_menu_item(&mut self, item: &dyn crate::IsMenuItem, op: AddOp) -> crate::Result<()> {
-        if is_item_supported!(item) {
+        if self.batch != Batch::Off {
+            self.batch = Batch::Pending;
+        } else if is_item_supported!(item) {
             for (menu_id, menu_bar) in &self.gtk_menubars {
                 let gtk_item =
                     item.make_gtk_menu_item(*menu_id, self.accel_group.as_ref(), true, true)?;
@@ -125,6 +156,53 @@ impl Menu {
             AddOp::Insert(position) => self.children.insert(position, item.child()),
         }
 
+        if self.batch == Batch::Off {
+            notify_observers(&self.observers, |o| o.layout_changed());
+        }
+
+        Ok(())
+    }
+
+    /// Defers creating the gtk items of the items added from now on until [`Self::end_batch`],
+    /// returns `false` if a batch was already started.
+    pub fn begin_batch(&mut self) -> bool {
+        let started = self.batch == Batch::Off;
+        if started {
+            self.batch = Batch::Started;
+        }
+        started
+    }
+
+    /// Creates the gtk items of the items added since [`Self::begin_batch`]
+    /// and puts every gtk item at the position of its item.
+    pub fn end_batch(&mut self) -> crate::Result<()> {
+        let pending = self.batch == Batch::Pending;
+        self.batch = Batch::Off;
+        if !pending {
+            return Ok(());
+        }
+
+        for (menu_id, menu_bar) in &self.gtk_menubars {
+            sync_children_in_menu(
+                &self.children,
+                *menu_id,
+                menu_bar,
+                self.accel_group.as_ref(),
+                true,
+            )?;
+        }
+        if let (menu_id, Some(menu)) = &self.gtk_menu {
+            sync_children_in_menu(
+                &self.children,
+                *menu_id,
+                menu,
+                self.accel_group.as_ref(),
+                false,
+            )?;
+        }
+
+        notify_observers(&self.observers, |o| o.layout_changed());
+
         Ok(())
     }
 
@@ -156,7 +234,9 @@ impl Menu {
     }
 
     pub fn remove(&mut self, item: &dyn crate::IsMenuItem) -> crate::Result<()> {
-        self.remove_inner(item, true, None)
+        self.remove_inner(item, true, None)?;
+        notify_observers(&self.observers, |o| o.layout_changed());
+        Ok(())
     }
 
     fn remove_inner(
@@ -242,6 +322,48 @@ impl Menu {
         Ok(())
     }
 
+    pub fn move_item(
+        &mut self,
+        item: &dyn crate::IsMenuItem,
+        position: usize,
+    ) -> crate::Result<()> {
+        let index = self
+            .children
+            .iter()
+            .position(|e| e.borrow().id == item.id())
+            .ok_or(crate::Error::NotAChildOfThisMenu)?;
+        let position = position.min(self.children.len() - 1);
+        let child = self.children.remove(index);
+        self.children.insert(position, child);
+
+        if self.batch != Batch::Off {
+            self.batch = Batch::Pending;
+        } else {
+            for (menu_id, menu_bar) in &self.gtk_menubars {
+                sync_children_in_menu(
+                    &self.children,
+                    *menu_id,
+                    menu_bar,
+                    self.accel_group.as_ref(),
+                    true,
+                )?;
+            }
+            if let (menu_id, Some(menu)) = &self.gtk_menu {
+                sync_children_in_menu(
+                    &self.children,
+                    *menu_id,
+                    menu,
+                    self.accel_group.as_ref(),
+                    false,
+                )?;
+            }
+        }
+
+        notify_observers(&self.observers, |o| o.layout_changed());
+
+        Ok(())
+    }
+
     pub fn items(&self) -> Vec<MenuItemKind> {
         self.children
             .iter()
@@ -249,8 +371,18 @@ impl Menu {
             .collect()
     }
 
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn children(&self) -> &[Rc<RefCell<MenuChild>>] {
+        &self.children
+    }
+
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn observers(&self) -> &MenuObservers {
+        &self.observers
+    }
+
     pub fn init_for_gtk_window<W, C>(
-        &mut self,
+        this: &Rc<RefCell<Self>>,
         window: &W,
         container: Option<&C>,
     ) -> crate::Result<()>
@@ -259,65 +391,121 @@ impl Menu {
         W: IsA<gtk::Container>,
         C: IsA<gtk::Container>,
     {
-        let id = window.as_ptr() as u32;
+        let menu_bar = Self::create_menubar_for_gtk_window(this, window)?;
 
-        if self.accel_group.is_none() {
-            self.accel_group = Some(gtk::AccelGroup::new());
+        // add the menubar to the specified widget, otherwise to the window
+        if let Some(container) = container {
+            if container.type_().name() == "GtkBox" {
+                let gtk_box = container.dynamic_cast_ref::<gtk::Box>().unwrap();
+                gtk_box.pack_start(&menu_bar, false, false, 0);
+                gtk_box.reorder_child(&menu_bar, 0);
+            } else {
+                container.add(&menu_bar);
+            }
+        } else {
+            window.add(&menu_bar);
+        }
+
+        Ok(())
+    }
+
+    /// Creates the menubar for `window` and attaches the accelerators to it,
+    /// the menubar isn't added to any container.
+    ///
+    /// The menubar is forgotten once `window` is destroyed.
+    pub fn create_menubar_for_gtk_window<W>(
+        this: &Rc<RefCell<Self>>,
+        window: &W,
+    ) -> crate::Result<gtk::MenuBar>
+    where
+        W: IsA<gtk::Window>,
+    {
+        let id = window_id(window);
+        let window = window.upcast_ref::<gtk::Window>();
+        let mut menu = this.borrow_mut();
+
+        if menu.accel_group.is_none() {
+            menu.accel_group = Some(gtk::AccelGroup::new());
         }
 
         // This is the first time this method has been called on this window
-        // so we need to create the menubar and its parent box
-        if let Entry::Vacant(e) = self.gtk_menubars.entry(id) {
+        // so we need to create the menubar
+        if let Entry::Vacant(e) = menu.gtk_menubars.entry(id) {
             let menu_bar = gtk::MenuBar::new();
             e.insert(menu_bar);
         } else {
             return Err(crate::Error::AlreadyInitialized);
         }
 
+        window.add_accel_group(menu.accel_group.as_ref().unwrap());
+
+        let this_ = Rc::downgrade(this);
+        let handler = window.connect_destroy(move |window| {
+            if let Some(this) = this_.upgrade() {
+                Self::forget_gtk_window(&this, window, id);
+            }
+        });
+        menu.gtk_windows.insert(id, (window.downgrade(), handler));
+
         // Construct the entries of the menubar
-        let menu_bar = &self.gtk_menubars[&id];
+        for item in menu.items() {
+            menu.add_menu_item_with_id(item.as_ref(), id)?;
+        }
 
-        window.add_accel_group(self.accel_group.as_ref().unwrap());
+        // Show the menubar
+        let menu_bar = menu.gtk_menubars[&id].clone();
+        menu_bar.show();
 
-        for item in self.items() {
-            self.add_menu_item_with_id(item.as_ref(), id)?;
+        Ok(menu_bar)
+    }
+
+    /// Drops the menubar of a destroyed window and detaches the accelerators from it.
+    fn forget_gtk_window(this: &Rc<RefCell<Self>>, window: &gtk::Window, id: u32) {
+        match this.try_borrow_mut() {
+            Ok(mut menu) => {
+                if menu.remove_menubar(id).is_some() {
+                    window.remove_accel_group(menu.accel_group.as_ref().unwrap());
+                }
+            }
+            // the window was destroyed while the menu is in use, try again later
+            Err(_) => {
+                let this = Rc::downgrade(this);
+                let window = window.clone();
+                glib::idle_add_local_once(move || {
+                    if let Some(this) = this.upgrade() {
+                        Self::forget_gtk_window(&this, &window, id);
+                    }
+                });
+            }
         }
+    }
 
-        // add the menubar to the specified widget, otherwise to the window
-        if let Some(container) = container {
-            if container.type_().name() == "GtkBox" {
-                let gtk_box = container.dynamic_cast_ref::<gtk::Box>().unwrap();
-                gtk_box.pack_start(menu_bar, false, false, 0);
-                gtk_box.reorder_child(menu_bar, 0);
-            } else {
-                container.add(menu_bar);
+    /// Removes the menubar with `id` and its items from our cache,
+    /// without removing it from the widget tree.
+    fn remove_menubar(&mut self, id: u32) -> Option<gtk::MenuBar> {
+        let menu_bar = self.gtk_menubars.remove(&id)?;
+
+        if let Some((window, handler)) = self.gtk_windows.remove(&id) {
+            if let Some(window) = window.upgrade() {
+                window.disconnect(handler);
             }
-        } else {
-            window.add(menu_bar);
         }
 
-        // Show the menubar
-        menu_bar.show();
+        for item in self.items() {
+            let _ = self.remove_inner(item.as_ref(), false, Some(id));
+        }
 
-        Ok(())
+        Some(menu_bar)
     }
 
     pub fn remove_for_gtk_window<W>(&mut self, window: &W) -> crate::Result<()>
     where
         W: IsA<gtk::Window>,
     {
-        let id = window.as_ptr() as u32;
-
-        // Remove from our cache
         let menu_bar = self
-            .gtk_menubars
-            .remove(&id)
+            .remove_menubar(window_id(window))
             .ok_or(crate::Error::NotInitialized)?;
 
-        for item in self.items() {
-            let _ = self.remove_inner(item.as_ref(), false, Some(id));
-        }
-
         // Remove the [`gtk::Menubar`] from the widget tree
         unsafe { menu_bar.destroy() };
         // Detach the accelerators from the window
@@ -330,7 +518,7 @@ impl Menu {
         W: IsA<gtk::Window>,
     {
         self.gtk_menubars
-            .get(&(window.as_ptr() as u32))
+            .get(&window_id(window))
             .ok_or(crate::Error::NotInitialized)?
             .hide();
         Ok(())
@@ -341,7 +529,7 @@ impl Menu {
         W: IsA<gtk::Window>,
     {
         self.gtk_menubars
-            .get(&(window.as_ptr() as u32))
+            .get(&window_id(window))
             .ok_or(crate::Error::NotInitialized)?
             .show_all();
         Ok(())
@@ -352,7 +540,7 @@ impl Menu {
         W: IsA<gtk::Window>,
     {
         self.gtk_menubars
-            .get(&(window.as_ptr() as u32))
+            .get(&window_id(window))
             .map(|m| m.get_visible())
             .unwrap_or(false)
     }
@@ -361,15 +549,18 @@ impl Menu {
     where
         W: gtk::prelude::IsA<gtk::Window>,
     {
-        self.gtk_menubars.get(&(window.as_ptr() as u32)).cloned()
+        self.gtk_menubars.get(&window_id(window)).cloned()
     }
 
-    pub fn show_context_menu_for_gtk_window(
-        &mut self,
-        widget: &impl IsA<gtk::Widget>,
-        position: Option<Position>,
-    ) -> bool {
-        show_context_menu(self.gtk_context_menu(), widget, position)
+    pub fn to_gio_menu_model(this: &Rc<RefCell<Self>>) -> (gio::Menu, gio::SimpleActionGroup) {
+        let export = this.borrow().gio_export.clone();
+        let export = export.unwrap_or_else(|| {
+            let export = MenuModelExport::new(this);
+            register_observer(&this.borrow().observers, export.clone());
+            this.borrow_mut().gio_export = Some(export.clone());
+            export
+        });
+        (export.model.clone(), export.actions.clone())
     }
 
     pub fn gtk_context_menu(&mut self) -> gtk::Menu {
@@ -397,31 +588,41 @@ impl Menu {
 pub struct MenuChild {
     // shared fields between submenus and menu items
     item_type: MenuItemType,
-    text: String,
-    enabled: bool,
-    id: MenuId,
+    pub(crate) text: String,
+    pub(crate) enabled: bool,
+    pub(crate) tooltip: Option<String>,
+    data: Option<Rc<dyn Any>>,
+    // the menus and submenus this item was added to, once per time it was added
+    pub(crate) parents: Vec<crate::menu_parent::WeakMenuParent>,
+    pub(crate) id: MenuId,
 
     gtk_menu_items: Rc<RefCell<HashMap<u32, Vec<gtk::MenuItem>>>>,
+    observers: MenuObservers,
+    batch: Batch,
 
     // menu item fields
-    accelerator: Option<Accelerator>,
+    pub(crate) accelerator: Option<Accelerator>,
     gtk_accelerator: Option<(gdk::ModifierType, u32)>,
 
     // predefined menu item fields
-    predefined_item_type: Option<PredefinedMenuItemType>,
+    pub(crate) predefined_item_type: Option<PredefinedMenuItemType>,
 
     // check menu item fields
     checked: Option<Rc<AtomicBool>>,
     is_syncing_checked_state: Option<Rc<AtomicBool>>,
 
     // icon menu item fields
-    icon: Option<Icon>,
+    pub(crate) icon: Option<Icon>,
+    icon_animation: Option<Rc<RefCell<IconAnimation>>>,
 
     // submenu fields
     pub children: Option<Vec<Rc<RefCell<MenuChild>>>>,
     gtk_menus: Option<HashMap<u32, Vec<(u32, gtk::Menu)>>>,
     gtk_menu: Option<(u32, Option<gtk::Menu>)>, // dedicated menu for tray or context menus
     accel_group: Option<gtk::AccelGroup>,
+    // the GTK4 context menu of this submenu
+    #[cfg(feature = "gtk4")]
+    pub(crate) gtk4: super::gtk4::Gtk4Submenu,
 }
 
 impl Drop for MenuChild {
@@ -440,6 +641,10 @@ impl Drop for MenuChild {
             }
         }
 
+        if let Some(animation) = &self.icon_animation {
+            animation.borrow_mut().stop();
+        }
+
         for items in self.gtk_menu_items.borrow().values() {
             for item in items {
                 if let Some(accel_group) = &self.accel_group {
@@ -487,6 +692,66 @@ fn drop_children_from_menu_and_destroy(
     }
 }
 
+/// Moves `item` to `position` in `menu`, keeping the widget and its accelerator.
+fn reorder_gtk_item(menu: &gtk::MenuShell, item: &gtk::MenuItem, position: i32) {
+    match menu.dynamic_cast_ref::<gtk::Menu>() {
+        Some(menu) => menu.reorder_child(item, position),
+        // `gtk::MenuBar` can't reorder, but removing keeps the widget alive
+        None => {
+            menu.remove(item);
+            menu.insert(item, position);
+        }
+    }
+}
+
+/// Creates the missing gtk items of `children` in the gtk menu `id`
+/// and puts every gtk item at the position of its child.
+///
+/// Unsupported children have no gtk item and take no position, and a child found
+/// more than once in `children` gets one of its gtk items in `menu` per occurrence.
+fn sync_children_in_menu(
+    children: &[Rc<RefCell<MenuChild>>],
+    id: u32,
+    menu: &impl IsA<gtk::MenuShell>,
+    accel_group: Option<&gtk::AccelGroup>,
+    for_menu_bar: bool,
+) -> crate::Result<()> {
+    let menu = menu.upcast_ref::<gtk::MenuShell>();
+    let mut position = 0;
+    let mut occurrences = HashMap::new();
+    for child in children {
+        let (kind, gtk_item) = {
+            let child_ = child.borrow();
+            if !child_.is_supported() {
+                continue;
+            }
+
+            let nth = occurrences.entry(Rc::as_ptr(child)).or_insert(0);
+            let gtk_item = child_.gtk_menu_items.borrow().get(&id).and_then(|items| {
+                items
+                    .iter()
+                    .filter(|i| i.parent().as_ref() == Some(menu.upcast_ref()))
+                    .nth(*nth)
+                    .cloned()
+            });
+            *nth += 1;
+            (child_.kind(child.clone()), gtk_item)
+        };
+
+        match gtk_item {
+            Some(gtk_item) => reorder_gtk_item(menu, &gtk_item, position),
+            None => {
+                let gtk_item = kind.make_gtk_menu_item(id, accel_group, true, for_menu_bar)?;
+                menu.insert(&gtk_item, position);
+                gtk_item.show();
+            }
+        }
+        position += 1;
+    }
+
+    Ok(())
+}
+
 /// Constructors
 impl MenuChild {
     pub fn new(
@@ -499,9 +764,13 @@ impl MenuChild {
             text: text.to_string(),
             enabled,
             accelerator,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             item_type: MenuItemType::MenuItem,
             gtk_menu_items: Rc::new(RefCell::new(HashMap::new())),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
             accel_group: None,
             checked: None,
             children: None,
@@ -509,7 +778,11 @@ impl MenuChild {
             gtk_menu: None,
             gtk_menus: None,
             icon: None,
+            icon_animation: None,
             is_syncing_checked_state: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -518,16 +791,24 @@ impl MenuChild {
         Self {
             text: text.to_string(),
             enabled,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             children: Some(Vec::new()),
             item_type: MenuItemType::Submenu,
             gtk_menu: Some((COUNTER.next(), None)),
             gtk_menu_items: Rc::new(RefCell::new(HashMap::new())),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
             gtk_menus: Some(HashMap::new()),
             accel_group: None,
             gtk_accelerator: None,
             icon: None,
+            icon_animation: None,
             is_syncing_checked_state: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
             accelerator: None,
             checked: None,
@@ -539,10 +820,17 @@ impl MenuChild {
             text: text.unwrap_or_else(|| item_type.text().to_string()),
             enabled: true,
             accelerator: item_type.accelerator(),
-            id: MenuId(COUNTER.next().to_string()),
+            id: MenuId::auto(COUNTER.next()),
             item_type: MenuItemType::Predefined,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: Some(item_type),
             gtk_menu_items: Rc::new(RefCell::new(HashMap::new())),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
             accel_group: None,
             checked: None,
             children: None,
@@ -550,6 +838,7 @@ impl MenuChild {
             gtk_menu: None,
             gtk_menus: None,
             icon: None,
+            icon_animation: None,
             is_syncing_checked_state: None,
         }
     }
@@ -567,15 +856,23 @@ impl MenuChild {
             checked: Some(Rc::new(AtomicBool::new(checked))),
             is_syncing_checked_state: Some(Rc::new(AtomicBool::new(false))),
             accelerator,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             item_type: MenuItemType::Check,
             gtk_menu_items: Rc::new(RefCell::new(HashMap::new())),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
             accel_group: None,
             children: None,
             gtk_accelerator: None,
             gtk_menu: None,
             gtk_menus: None,
             icon: None,
+            icon_animation: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -591,10 +888,18 @@ impl MenuChild {
             text: text.to_string(),
             enabled,
             icon,
+            icon_animation: Some(Rc::new(RefCell::new(IconAnimation {
+                grayscale: !enabled,
+                ..Default::default()
+            }))),
             accelerator,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             item_type: MenuItemType::Icon,
             gtk_menu_items: Rc::new(RefCell::new(HashMap::new())),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
             accel_group: None,
             checked: None,
             children: None,
@@ -602,6 +907,9 @@ impl MenuChild {
             gtk_menu: None,
             gtk_menus: None,
             is_syncing_checked_state: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -617,9 +925,13 @@ impl MenuChild {
             text: text.to_string(),
             enabled,
             accelerator,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             item_type: MenuItemType::Icon,
             gtk_menu_items: Rc::new(RefCell::new(HashMap::new())),
+            observers: Default::default(),
+            #[cfg(feature = "gtk4")]
+            gtk4: Default::default(),
+            batch: Batch::Off,
             accel_group: None,
             checked: None,
             children: None,
@@ -627,14 +939,71 @@ impl MenuChild {
             gtk_menu: None,
             gtk_menus: None,
             icon: None,
+            icon_animation: Some(Rc::new(RefCell::new(IconAnimation {
+                grayscale: !enabled,
+                ..Default::default()
+            }))),
             is_syncing_checked_state: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
+
+    /// Creates the icon menu item of [`MenuChild::duplicate`],
+    /// a running icon animation is copied and starts over from its first frame.
+    pub(crate) fn duplicate_icon(&self, id: Option<MenuId>) -> Self {
+        let mut child = Self::new_icon(
+            &self.text,
+            self.enabled,
+            self.icon.clone(),
+            self.accelerator,
+            id,
+        );
+        let animation = self.icon_animation.as_ref().map(|a| a.borrow());
+        if let Some(animation) = animation.filter(|a| !a.frames.is_empty()) {
+            child.set_animated_icon(animation.frames.clone(), animation.interval);
+        }
+        child
+    }
 }
 
 /// Shared methods
 impl MenuChild {
+    /// Whether this item can be displayed on gtk, unsupported
+    /// predefined menu items are kept in menus but never displayed.
+    pub(crate) fn is_supported(&self) -> bool {
+        if let Some(predefined_item_type) = &self.predefined_item_type {
+            // undo and redo only have keys to send, see `activate_predefined_item`
+            #[cfg(feature = "libxdo")]
+            if matches!(
+                predefined_item_type,
+                PredefinedMenuItemType::Undo | PredefinedMenuItemType::Redo
+            ) {
+                return true;
+            }
+
+            matches!(
+                predefined_item_type,
+                PredefinedMenuItemType::Separator
+                    | PredefinedMenuItemType::Copy
+                    | PredefinedMenuItemType::Cut
+                    | PredefinedMenuItemType::Paste
+                    | PredefinedMenuItemType::SelectAll
+                    | PredefinedMenuItemType::Minimize
+                    | PredefinedMenuItemType::Maximize
+                    | PredefinedMenuItemType::Fullscreen
+                    | PredefinedMenuItemType::Hide
+                    | PredefinedMenuItemType::CloseWindow
+                    | PredefinedMenuItemType::Quit
+                    | PredefinedMenuItemType::About(_)
+            )
+        } else {
+            true
+        }
+    }
+
     pub(crate) fn item_type(&self) -> MenuItemType {
         self.item_type
     }
@@ -666,6 +1035,28 @@ impl MenuChild {
                 i.set_label(&text);
             }
         }
+        notify_observers(&self.observers, |o| o.layout_changed());
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        self.tooltip.clone()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        self.tooltip = tooltip.map(ToString::to_string);
+        for items in self.gtk_menu_items.borrow().values() {
+            for i in items {
+                i.set_tooltip_text(tooltip);
+            }
+        }
+    }
+
+    pub fn data(&self) -> Option<Rc<dyn Any>> {
+        self.data.clone()
+    }
+
+    pub fn set_data(&mut self, data: Option<Rc<dyn Any>>) {
+        self.data = data;
     }
 
     pub fn is_enabled(&self) -> bool {
@@ -685,11 +1076,34 @@ impl MenuChild {
 
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
+        if let Some(animation) = &self.icon_animation {
+            animation.borrow_mut().grayscale = !enabled;
+        }
         for items in self.gtk_menu_items.borrow().values() {
             for i in items {
                 i.set_sensitive(enabled);
             }
         }
+        notify_observers(&self.observers, |o| o.enabled_changed(&self.id, enabled));
+
+        if self.item_type == MenuItemType::Icon {
+            self.update_icon_images();
+        }
+    }
+
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn accelerator(&self) -> Option<&Accelerator> {
+        self.accelerator.as_ref()
+    }
+
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn predefined_item_type(&self) -> Option<&PredefinedMenuItemType> {
+        self.predefined_item_type.as_ref()
+    }
+
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn observers(&self) -> &MenuObservers {
+        &self.observers
     }
 
     pub fn set_accelerator(&mut self, accelerator: Option<Accelerator>) -> crate::Result<()> {
@@ -719,6 +1133,7 @@ impl MenuChild {
 
         self.gtk_accelerator = new_accel;
         self.accelerator = accelerator;
+        notify_observers(&self.observers, |o| o.layout_changed());
 
         Ok(())
     }
@@ -756,31 +1171,178 @@ impl MenuChild {
             }
         }
         is_syncing.store(false, Ordering::Release);
+        notify_observers(&self.observers, |o| o.checked_changed(&self.id, checked));
     }
 }
 
 /// IconMenuItem methods
 impl MenuChild {
+    #[cfg(feature = "gtk4")]
+    pub(crate) fn icon(&self) -> Option<&Icon> {
+        self.icon.as_ref()
+    }
+
     pub fn set_icon(&mut self, icon: Option<Icon>) {
-        self.icon.clone_from(&icon);
+        if let Some(animation) = &self.icon_animation {
+            animation.borrow_mut().stop();
+        }
+        self.icon = icon;
+        self.update_icon_images();
+        notify_observers(&self.observers, |o| o.layout_changed());
+    }
 
-        let pixbuf = icon.map(|i| i.inner.to_pixbuf_scale(16, 16));
-        for items in self.gtk_menu_items.borrow().values() {
-            for i in items {
-                let box_container = i.child().unwrap().downcast::<gtk::Box>().unwrap();
-                box_container.children()[0]
-                    .downcast_ref::<gtk::Image>()
-                    .unwrap()
-                    .set_pixbuf(pixbuf.as_ref())
+    pub fn set_animated_icon(&mut self, frames: Vec<Icon>, interval: Duration) {
+        if frames.len() < 2 {
+            return self.set_icon(frames.into_iter().next());
+        }
+
+        // shown whenever the animation is stopped
+        self.icon = frames.first().cloned();
+
+        let animation = self.icon_animation.clone().unwrap();
+        {
+            let mut animation = animation.borrow_mut();
+            animation.stop();
+            animation.frames = frames;
+            animation.interval = interval;
+        }
+        self.update_icon_images();
+        IconAnimation::start(&animation, &self.gtk_menu_items);
+    }
+
+    /// GTK only dims the label of insensitive items,
+    /// so disabled items get a grayscale variant of their icon instead.
+    fn icon_pixbuf(&self) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+        let animation = self.icon_animation.as_ref().map(|a| a.borrow());
+        animation
+            .as_ref()
+            .and_then(|a| a.current_frame())
+            .or(self.icon.as_ref())
+            .and_then(|icon| {
+                icon.inner
+                    .to_cached_pixbuf_scale(16, 16, !self.enabled)
+                    .ok()
+            })
+    }
+
+    fn update_icon_images(&self) {
+        set_icon_images(&self.gtk_menu_items.borrow(), self.icon_pixbuf().as_ref());
+    }
+
+    /// Runs the animation, if any, while `image` is mapped
+    /// and pauses it once all the images of this item are unmapped.
+    fn connect_icon_animation(&self, image: &gtk::Image) {
+        let Some(animation) = &self.icon_animation else {
+            return;
+        };
+
+        let weak_animation = Rc::downgrade(animation);
+        let weak_items = Rc::downgrade(&self.gtk_menu_items);
+        image.connect_map(move |_| {
+            if let (Some(animation), Some(items)) = (weak_animation.upgrade(), weak_items.upgrade())
+            {
+                animation.borrow_mut().mapped += 1;
+                IconAnimation::start(&animation, &items);
             }
+        });
+
+        let weak_animation = Rc::downgrade(animation);
+        image.connect_unmap(move |_| {
+            if let Some(animation) = weak_animation.upgrade() {
+                let mut animation = animation.borrow_mut();
+                animation.mapped = animation.mapped.saturating_sub(1);
+                if animation.mapped == 0 {
+                    animation.pause();
+                }
+            }
+        });
+    }
+}
+
+fn set_icon_images(
+    items: &HashMap<u32, Vec<gtk::MenuItem>>,
+    pixbuf: Option<&gtk::gdk_pixbuf::Pixbuf>,
+) {
+    for items in items.values() {
+        for i in items {
+            let box_container = i.child().unwrap().downcast::<gtk::Box>().unwrap();
+            box_container.children()[0]
+                .downcast_ref::<gtk::Image>()
+                .unwrap()
+                .set_pixbuf(pixbuf)
         }
     }
 }
 
+/// Frames of an animated icon, shared between an icon menu item
+/// and the `map`/`unmap` handlers of its images.
+#[derive(Debug, Default)]
+struct IconAnimation {
+    frames: Vec<Icon>,
+    interval: Duration,
+    frame: usize,
+    grayscale: bool,
+    // number of images of the item that are currently mapped
+    mapped: usize,
+    timer: Option<glib::SourceId>,
+}
+
+impl IconAnimation {
+    fn current_frame(&self) -> Option<&Icon> {
+        self.frames.get(self.frame)
+    }
+
+    fn start(
+        animation: &Rc<RefCell<IconAnimation>>,
+        items: &Rc<RefCell<HashMap<u32, Vec<gtk::MenuItem>>>>,
+    ) {
+        let mut state = animation.borrow_mut();
+        if state.timer.is_some() || state.frames.len() < 2 || state.mapped == 0 {
+            return;
+        }
+
+        let weak_animation = Rc::downgrade(animation);
+        let weak_items = Rc::downgrade(items);
+        state.timer = Some(glib::timeout_add_local(state.interval, move || {
+            let (Some(animation), Some(items)) = (weak_animation.upgrade(), weak_items.upgrade())
+            else {
+                return glib::ControlFlow::Break;
+            };
+
+            let pixbuf = {
+                let mut animation = animation.borrow_mut();
+                animation.frame = (animation.frame + 1) % animation.frames.len();
+                let grayscale = animation.grayscale;
+                animation
+                    .current_frame()
+                    .and_then(|icon| icon.inner.to_cached_pixbuf_scale(16, 16, grayscale).ok())
+            };
+            set_icon_images(&items.borrow(), pixbuf.as_ref());
+
+            glib::ControlFlow::Continue
+        }));
+    }
+
+    /// Stops the timer but keeps the current frame.
+    fn pause(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.remove();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.pause();
+        self.frames.clear();
+        self.frame = 0;
+    }
+}
+
 /// Submenu methods
 impl MenuChild {
     pub fn add_menu_item(&mut self, item: &dyn crate::IsMenuItem, op: AddOp) -> crate::Result<()> {
-        if is_item_supported!(item) {
+        if self.batch != Batch::Off {
+            self.batch = Batch::Pending;
+        } else if is_item_supported!(item) {
             for menus in self.gtk_menus.as_ref().unwrap().values() {
                 for (menu_id, menu) in menus {
                     let gtk_item =
@@ -815,6 +1377,44 @@ impl MenuChild {
                 .insert(position, item.child()),
         }
 
+        if self.batch == Batch::Off {
+            notify_observers(&self.observers, |o| o.layout_changed());
+        }
+
+        Ok(())
+    }
+
+    /// Defers creating the gtk items of the items added from now on until [`Self::end_batch`],
+    /// returns `false` if a batch was already started.
+    pub fn begin_batch(&mut self) -> bool {
+        let started = self.batch == Batch::Off;
+        if started {
+            self.batch = Batch::Started;
+        }
+        started
+    }
+
+    /// Creates the gtk items of the items added since [`Self::begin_batch`]
+    /// and puts every gtk item at the position of its item.
+    pub fn end_batch(&mut self) -> crate::Result<()> {
+        let pending = self.batch == Batch::Pending;
+        self.batch = Batch::Off;
+        if !pending {
+            return Ok(());
+        }
+
+        let children = self.children.as_ref().unwrap();
+        for menus in self.gtk_menus.as_ref().unwrap().values() {
+            for (menu_id, menu) in menus {
+                sync_children_in_menu(children, *menu_id, menu, self.accel_group.as_ref(), false)?;
+            }
+        }
+        if let (menu_id, Some(menu)) = self.gtk_menu.as_ref().unwrap() {
+            sync_children_in_menu(children, *menu_id, menu, self.accel_group.as_ref(), false)?;
+        }
+
+        notify_observers(&self.observers, |o| o.layout_changed());
+
         Ok(())
     }
 
@@ -848,7 +1448,9 @@ impl MenuChild {
     }
 
     pub fn remove(&mut self, item: &dyn crate::IsMenuItem) -> crate::Result<()> {
-        self.remove_inner(item, true, None)
+        self.remove_inner(item, true, None)?;
+        notify_observers(&self.observers, |o| o.layout_changed());
+        Ok(())
     }
 
     fn remove_inner(
@@ -939,6 +1541,45 @@ impl MenuChild {
         Ok(())
     }
 
+    pub fn move_item(
+        &mut self,
+        item: &dyn crate::IsMenuItem,
+        position: usize,
+    ) -> crate::Result<()> {
+        let children = self.children.as_mut().unwrap();
+        let index = children
+            .iter()
+            .position(|e| e.borrow().id == item.id())
+            .ok_or(crate::Error::NotAChildOfThisMenu)?;
+        let position = position.min(children.len() - 1);
+        let child = children.remove(index);
+        children.insert(position, child);
+
+        if self.batch != Batch::Off {
+            self.batch = Batch::Pending;
+        } else {
+            let children = self.children.as_ref().unwrap();
+            for menus in self.gtk_menus.as_ref().unwrap().values() {
+                for (menu_id, menu) in menus {
+                    sync_children_in_menu(
+                        children,
+                        *menu_id,
+                        menu,
+                        self.accel_group.as_ref(),
+                        false,
+                    )?;
+                }
+            }
+            if let (menu_id, Some(menu)) = self.gtk_menu.as_ref().unwrap() {
+                sync_children_in_menu(children, *menu_id, menu, self.accel_group.as_ref(), false)?;
+            }
+        }
+
+        notify_observers(&self.observers, |o| o.layout_changed());
+
+        Ok(())
+    }
+
     pub fn items(&self) -> Vec<MenuItemKind> {
         self.children
             .as_ref()
@@ -948,14 +1589,6 @@ impl MenuChild {
             .collect()
     }
 
-    pub fn show_context_menu_for_gtk_window(
-        &mut self,
-        widget: &impl IsA<gtk::Widget>,
-        position: Option<Position>,
-    ) -> bool {
-        show_context_menu(self.gtk_context_menu(), widget, position)
-    }
-
     pub fn gtk_context_menu(&mut self) -> gtk::Menu {
         let mut add_items = false;
         {
@@ -1122,7 +1755,9 @@ impl MenuChild {
             PredefinedMenuItemType::Copy
             | PredefinedMenuItemType::Cut
             | PredefinedMenuItemType::Paste
-            | PredefinedMenuItemType::SelectAll => {
+            | PredefinedMenuItemType::SelectAll
+            | PredefinedMenuItemType::Undo
+            | PredefinedMenuItemType::Redo => {
                 let item = make_item();
                 let (mods, key) =
                     parse_accelerator(&predefined_item_type.accelerator().unwrap()).unwrap();
@@ -1131,62 +1766,22 @@ impl MenuChild {
                     .downcast::<gtk::AccelLabel>()
                     .unwrap()
                     .set_accel(key, mods);
-                item.connect_activate(move |_| {
-                    // TODO: wayland
-                    #[cfg(feature = "libxdo")]
-                    if let Ok(xdo) = libxdo::XDo::new(None) {
-                        let _ = xdo.send_keysequence(predefined_item_type.xdo_keys(), 0);
-                    }
-                });
                 item
             }
-            PredefinedMenuItemType::About(metadata) => {
+            _ => {
                 let item = make_item();
                 register_accel(&item);
-                item.connect_activate(move |_| {
-                    if let Some(metadata) = &metadata {
-                        let mut builder = AboutDialog::builder().modal(true).resizable(false);
-
-                        if let Some(name) = &metadata.name {
-                            builder = builder.program_name(name);
-                        }
-                        if let Some(version) = &metadata.full_version() {
-                            builder = builder.version(version);
-                        }
-                        if let Some(authors) = &metadata.authors {
-                            builder = builder.authors(authors.clone());
-                        }
-                        if let Some(comments) = &metadata.comments {
-                            builder = builder.comments(comments);
-                        }
-                        if let Some(copyright) = &metadata.copyright {
-                            builder = builder.copyright(copyright);
-                        }
-                        if let Some(license) = &metadata.license {
-                            builder = builder.license(license);
-                        }
-                        if let Some(website) = &metadata.website {
-                            builder = builder.website(website);
-                        }
-                        if let Some(website_label) = &metadata.website_label {
-                            builder = builder.website_label(website_label);
-                        }
-                        if let Some(icon) = &metadata.icon {
-                            builder = builder.logo(&icon.inner.to_pixbuf());
-                        }
-
-                        let about = builder.build();
-                        about.run();
-                        unsafe {
-                            about.destroy();
-                        }
-                    }
-                });
                 item
             }
-            _ => unreachable!(),
         };
 
+        if !matches!(predefined_item_type, PredefinedMenuItemType::Separator) {
+            let id = self.id.clone();
+            item.connect_activate(move |item| {
+                activate_predefined_item(&predefined_item_type, &id, toplevel_window(item))
+            });
+        }
+
         if add_to_cache {
             self.gtk_menu_items
                 .borrow_mut()
@@ -1218,6 +1813,7 @@ impl MenuChild {
         let is_syncing_checked_state = self.is_syncing_checked_state.clone().unwrap();
         let checked = self.checked.clone().unwrap();
         let store = self.gtk_menu_items.clone();
+        let observers = self.observers.clone();
         item.connect_toggled(move |i| {
             let should_dispatch = is_syncing_checked_state
                 .compare_exchange(false, true, Ordering::Release, Ordering::Relaxed)
@@ -1236,6 +1832,7 @@ impl MenuChild {
                 }
 
                 is_syncing_checked_state.store(false, Ordering::Release);
+                notify_observers(&observers, |o| o.checked_changed(&id, c));
 
                 MenuEvent::send(crate::MenuEvent { id: id.clone() });
             }
@@ -1262,10 +1859,10 @@ impl MenuChild {
         for_menu_bar: bool,
     ) -> crate::Result<gtk::MenuItem> {
         let image = self
-            .icon
-            .as_ref()
-            .map(|i| gtk::Image::from_pixbuf(Some(&i.inner.to_pixbuf_scale(16, 16))))
+            .icon_pixbuf()
+            .map(|p| gtk::Image::from_pixbuf(Some(&p)))
             .unwrap_or_default();
+        self.connect_icon_animation(&image);
 
         self.accel_group = accel_group.cloned();
 
@@ -1324,7 +1921,7 @@ impl MenuItemKind {
         for_menu_bar: bool,
     ) -> crate::Result<gtk::MenuItem> {
         let mut child = self.child_mut();
-        match child.item_type() {
+        let item = match child.item_type() {
             MenuItemType::Submenu => {
                 child.create_gtk_item_for_submenu(menu_id, accel_group, add_to_cache)
             }
@@ -1343,7 +1940,18 @@ impl MenuItemKind {
                 add_to_cache,
                 for_menu_bar,
             ),
-        }
+        }?;
+
+        item.set_tooltip_text(child.tooltip.as_deref());
+        let id = child.id.clone();
+        item.connect_select(move |item| {
+            MenuHighlightEvent::send(crate::MenuHighlightEvent {
+                id: id.clone(),
+                tooltip: item.tooltip_text().map(Into::into),
+            });
+        });
+
+        Ok(item)
     }
 }
 
@@ -1360,22 +1968,18 @@ impl dyn IsMenuItem + '_ {
     }
 }
 
-fn show_context_menu(
-    gtk_menu: gtk::Menu,
+/// Pops up `gtk_menu` as a context menu anchored as described by `options`.
+///
+/// Returns `false` if the menu couldn't be shown.
+fn popup_context_menu(
+    gtk_menu: &gtk::Menu,
     widget: &impl IsA<gtk::Widget>,
-    position: Option<Position>,
+    options: &ContextMenuOptions,
 ) -> bool {
-    let (pos, window) = if let Some(pos) = position {
-        let window = widget.window();
-        (
-            pos.to_logical::<i32>(window.as_ref().map(|w| w.scale_factor()).unwrap_or(1) as _)
-                .into(),
-            window,
-        )
-    } else {
-        let window = widget.screen().and_then(|s| s.root_window());
-        (
-            window
+    let (rect, window) = match &options.anchor {
+        ContextMenuAnchor::Cursor => {
+            let window = widget.screen().and_then(|s| s.root_window());
+            let (x, y) = window
                 .as_ref()
                 .and_then(|w| {
                     w.display()
@@ -1386,65 +1990,186 @@ fn show_context_menu(
                             (p.1, p.2)
                         })
                 })
-                .unwrap_or_default(),
-            window,
-        )
+                .unwrap_or_default();
+            (gdk::Rectangle::new(x, y, 0, 0), window)
+        }
+        ContextMenuAnchor::Position(position) => {
+            let window = widget.window();
+            let scale_factor = window.as_ref().map(|w| w.scale_factor()).unwrap_or(1) as f64;
+            let (x, y) = position.to_logical::<i32>(scale_factor).into();
+            (gdk::Rectangle::new(x, y, 0, 0), window)
+        }
+        ContextMenuAnchor::Rect { position, size } => {
+            let window = widget.window();
+            let scale_factor = window.as_ref().map(|w| w.scale_factor()).unwrap_or(1) as f64;
+            let (x, y) = position.to_logical::<i32>(scale_factor).into();
+            let (width, height) = size.to_logical::<i32>(scale_factor).into();
+            (gdk::Rectangle::new(x, y, width, height), window)
+        }
+        ContextMenuAnchor::Widget(anchor) => {
+            let Some(window) = anchor.window() else {
+                return false;
+            };
+            let event = popup_trigger_event(&window);
+            gtk_menu.set_anchor_hints(options.anchor_hints.into());
+            gtk_menu.popup_at_widget(
+                anchor,
+                options.anchor_gravity.into(),
+                options.menu_gravity.into(),
+                Some(&event),
+            );
+            return true;
+        }
+        #[cfg(feature = "gtk4")]
+        ContextMenuAnchor::Gtk4Widget(_) => return false,
     };
 
-    if let Some(window) = window {
-        let mut event = gdk::Event::new(gdk::EventType::ButtonPress);
-        event.set_device(
-            window
-                .display()
-                .default_seat()
-                .and_then(|d| d.pointer())
-                .as_ref(),
-        );
+    let Some(window) = window else {
+        return false;
+    };
 
-        // Set the time of the event otherwise GTK will close the menu
-        // when right click is released
-        let event_ffi: *mut gdk::ffi::GdkEvent = event.to_glib_none().0;
-        if !event_ffi.is_null() {
-            let time = glib::monotonic_time() / 1000;
-            unsafe {
-                (*event_ffi).button.time = time as _;
-            }
+    let event = popup_trigger_event(&window);
+    gtk_menu.set_anchor_hints(options.anchor_hints.into());
+    gtk_menu.popup_at_rect(
+        &window,
+        &rect,
+        options.anchor_gravity.into(),
+        options.menu_gravity.into(),
+        Some(&event),
+    );
+
+    true
+}
+
+/// Creates the button press event a context menu is popped up for.
+fn popup_trigger_event(window: &gdk::Window) -> gdk::Event {
+    let mut event = gdk::Event::new(gdk::EventType::ButtonPress);
+    event.set_device(
+        window
+            .display()
+            .default_seat()
+            .and_then(|d| d.pointer())
+            .as_ref(),
+    );
+
+    // Set the time of the event otherwise GTK will close the menu
+    // when right click is released
+    let event_ffi: *mut gdk::ffi::GdkEvent = event.to_glib_none().0;
+    if !event_ffi.is_null() {
+        let time = glib::monotonic_time() / 1000;
+        unsafe {
+            (*event_ffi).button.time = time as _;
         }
+    }
 
-        let (tx, rx) = crossbeam_channel::unbounded();
-        let tx_clone = tx.clone();
-        let id = gtk_menu.connect_cancel(move |_| tx_clone.send(false).unwrap_or(()));
-        let id2 = gtk_menu.connect_selection_done(move |_| tx.send(true).unwrap_or(()));
+    event
+}
 
-        gtk_menu.popup_at_rect(
-            &window,
-            &gdk::Rectangle::new(pos.0, pos.1, 0, 0),
-            gdk::Gravity::NorthWest,
-            gdk::Gravity::NorthWest,
-            Some(&event),
-        );
+impl From<ContextMenuGravity> for gdk::Gravity {
+    fn from(gravity: ContextMenuGravity) -> Self {
+        match gravity {
+            ContextMenuGravity::NorthWest => gdk::Gravity::NorthWest,
+            ContextMenuGravity::North => gdk::Gravity::North,
+            ContextMenuGravity::NorthEast => gdk::Gravity::NorthEast,
+            ContextMenuGravity::West => gdk::Gravity::West,
+            ContextMenuGravity::Center => gdk::Gravity::Center,
+            ContextMenuGravity::East => gdk::Gravity::East,
+            ContextMenuGravity::SouthWest => gdk::Gravity::SouthWest,
+            ContextMenuGravity::South => gdk::Gravity::South,
+            ContextMenuGravity::SouthEast => gdk::Gravity::SouthEast,
+        }
+    }
+}
 
-        loop {
-            gtk::main_iteration();
+impl From<ContextMenuAnchorHints> for gdk::AnchorHints {
+    fn from(hints: ContextMenuAnchorHints) -> Self {
+        let mut flags = gdk::AnchorHints::empty();
+        flags.set(gdk::AnchorHints::FLIP_X, hints.flip_x);
+        flags.set(gdk::AnchorHints::FLIP_Y, hints.flip_y);
+        flags.set(gdk::AnchorHints::SLIDE_X, hints.slide_x);
+        flags.set(gdk::AnchorHints::SLIDE_Y, hints.slide_y);
+        flags.set(gdk::AnchorHints::RESIZE_X, hints.resize_x);
+        flags.set(gdk::AnchorHints::RESIZE_Y, hints.resize_y);
+        flags
+    }
+}
 
-            match rx.try_recv() {
-                Ok(result) => {
+/// Shows `gtk_menu` as a context menu and iterates the main loop until it is closed.
+pub(crate) fn show_context_menu(
+    gtk_menu: gtk::Menu,
+    widget: &impl IsA<gtk::Widget>,
+    options: &ContextMenuOptions,
+) -> bool {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let tx_clone = tx.clone();
+    let id = gtk_menu.connect_cancel(move |_| tx_clone.send(false).unwrap_or(()));
+    let id2 = gtk_menu.connect_selection_done(move |_| tx.send(true).unwrap_or(()));
+
+    if !popup_context_menu(&gtk_menu, widget, options) {
+        gtk_menu.disconnect(id);
+        gtk_menu.disconnect(id2);
+        return false;
+    }
+
+    loop {
+        gtk::main_iteration();
+
+        match rx.try_recv() {
+            Ok(result) => {
+                gtk_menu.disconnect(id);
+                gtk_menu.disconnect(id2);
+                return result;
+            }
+            Err(err) => {
+                if err.is_disconnected() {
                     gtk_menu.disconnect(id);
                     gtk_menu.disconnect(id2);
-                    return result;
-                }
-                Err(err) => {
-                    if err.is_disconnected() {
-                        gtk_menu.disconnect(id);
-                        gtk_menu.disconnect(id2);
-                        return false;
-                    }
+                    return false;
                 }
             }
         }
     }
+}
 
-    false
+/// Shows `gtk_menu` as a context menu and returns right away,
+/// `callback` is called from the main loop once the menu is closed.
+pub(crate) fn show_context_menu_with_callback(
+    gtk_menu: gtk::Menu,
+    widget: &impl IsA<gtk::Widget>,
+    options: &ContextMenuOptions,
+    callback: Box<dyn FnOnce(bool)>,
+) {
+    let handlers = Rc::new(RefCell::new(Vec::new()));
+    let callback = Rc::new(Cell::new(Some(callback)));
+
+    let finish = {
+        let gtk_menu = gtk_menu.clone();
+        let handlers = handlers.clone();
+        Rc::new(move |result: bool| {
+            let Some(callback) = callback.take() else {
+                return;
+            };
+            for id in handlers.borrow_mut().drain(..) {
+                gtk_menu.disconnect(id);
+            }
+            // the signals are emitted while GTK is handling the menu,
+            // so defer the callback to not run it from inside them
+            glib::idle_add_local_once(move || callback(result));
+        })
+    };
+
+    let finish_ = finish.clone();
+    handlers
+        .borrow_mut()
+        .push(gtk_menu.connect_cancel(move |_| finish_(false)));
+    let finish_ = finish.clone();
+    handlers
+        .borrow_mut()
+        .push(gtk_menu.connect_selection_done(move |_| finish_(true)));
+
+    if !popup_context_menu(&gtk_menu, widget, options) {
+        finish(false);
+    }
 }
 
 impl PredefinedMenuItemType {
@@ -1455,7 +2180,836 @@ impl PredefinedMenuItemType {
             PredefinedMenuItemType::Cut => "ctrl+X",
             PredefinedMenuItemType::Paste => "ctrl+v",
             PredefinedMenuItemType::SelectAll => "ctrl+a",
+            PredefinedMenuItemType::Undo => "ctrl+z",
+            PredefinedMenuItemType::Redo => "ctrl+y",
             _ => unreachable!(),
         }
     }
 }
+
+/// Activates `child` the same way its gtk menu items do, used by exported menus.
+fn activate_child(child: &Rc<RefCell<MenuChild>>) {
+    let (item_type, id, predefined_item_type) = {
+        let child = child.borrow();
+        (
+            child.item_type,
+            child.id.clone(),
+            child.predefined_item_type.clone(),
+        )
+    };
+
+    match item_type {
+        MenuItemType::Check => {
+            let checked = !child.borrow().is_checked();
+            child.borrow_mut().set_checked(checked);
+            MenuEvent::send(crate::MenuEvent { id });
+        }
+        MenuItemType::Predefined => {
+            activate_predefined_item(&predefined_item_type.unwrap(), &id, active_window())
+        }
+        _ => MenuEvent::send(crate::MenuEvent { id }),
+    }
+}
+
+/// Performs the action of a predefined menu item, `window` is the window
+/// that owns the menu the item was activated from, if any.
+fn activate_predefined_item(
+    item_type: &PredefinedMenuItemType,
+    id: &MenuId,
+    window: Option<gtk::Window>,
+) {
+    match item_type {
+        PredefinedMenuItemType::Copy
+        | PredefinedMenuItemType::Cut
+        | PredefinedMenuItemType::Paste
+        | PredefinedMenuItemType::SelectAll
+        | PredefinedMenuItemType::Undo
+        | PredefinedMenuItemType::Redo
+            if !emit_clipboard_signal(window.as_ref(), item_type) =>
+        {
+            // only works on X11
+            #[cfg(feature = "libxdo")]
+            if let Ok(xdo) = libxdo::XDo::new(None) {
+                let _ = xdo.send_keysequence(item_type.xdo_keys(), 0);
+            }
+        }
+        PredefinedMenuItemType::Minimize
+        | PredefinedMenuItemType::Maximize
+        | PredefinedMenuItemType::Fullscreen
+        | PredefinedMenuItemType::Hide
+        | PredefinedMenuItemType::CloseWindow => {
+            let Some(window) = window else {
+                return;
+            };
+            match item_type {
+                PredefinedMenuItemType::Minimize => window.iconify(),
+                PredefinedMenuItemType::Maximize => {
+                    if window.is_maximized() {
+                        window.unmaximize()
+                    } else {
+                        window.maximize()
+                    }
+                }
+                PredefinedMenuItemType::Fullscreen => {
+                    let is_fullscreen = window
+                        .window()
+                        .is_some_and(|w| w.state().contains(gdk::WindowState::FULLSCREEN));
+                    if is_fullscreen {
+                        window.unfullscreen()
+                    } else {
+                        window.fullscreen()
+                    }
+                }
+                PredefinedMenuItemType::Hide => window.hide(),
+                PredefinedMenuItemType::CloseWindow => window.close(),
+                _ => unreachable!(),
+            }
+        }
+        PredefinedMenuItemType::Quit => {
+            MenuEvent::send(crate::MenuEvent { id: id.clone() });
+            if gtk::main_level() > 0 {
+                gtk::main_quit();
+            }
+        }
+        PredefinedMenuItemType::About(Some(metadata)) => {
+            let dialog = ABOUT_DIALOG_HANDLER
+                .with(|handler| handler.borrow().as_ref().map(|handler| handler(metadata)));
+            let dialog = dialog.unwrap_or_else(|| about_dialog(metadata).upcast());
+            dialog.set_transient_for(window.as_ref());
+            dialog.show();
+        }
+        _ => {}
+    }
+}
+
+/// Builds the default, non-modal, about dialog for `metadata`.
+fn about_dialog(metadata: &AboutMetadata) -> AboutDialog {
+    let mut builder = AboutDialog::builder().resizable(false);
+
+    if let Some(name) = &metadata.name {
+        builder = builder.program_name(name);
+    }
+    if let Some(version) = &metadata.full_version() {
+        builder = builder.version(version);
+    }
+    if let Some(authors) = &metadata.authors {
+        builder = builder.authors(authors.clone());
+    }
+    if let Some(artists) = &metadata.artists {
+        builder = builder.artists(artists.clone());
+    }
+    if let Some(documenters) = &metadata.documenters {
+        builder = builder.documenters(documenters.clone());
+    }
+    if let Some(translators) = &metadata.translators {
+        builder = builder.translator_credits(translators);
+    }
+    if let Some(comments) = &metadata.comments {
+        builder = builder.comments(comments);
+    }
+    if let Some(copyright) = &metadata.copyright {
+        builder = builder.copyright(copyright);
+    }
+    if let Some(license_kind) = metadata.license_kind {
+        builder = builder.license_type(license_kind.into());
+    } else if let Some(license) = &metadata.license {
+        builder = builder.license(license);
+    }
+    if let Some(website) = &metadata.website {
+        builder = builder.website(website);
+    }
+    if let Some(website_label) = &metadata.website_label {
+        builder = builder.website_label(website_label);
+    }
+    if let Some(icon) = &metadata.icon {
+        builder = builder.logo(&icon.inner.to_pixbuf());
+    }
+
+    let about = builder.build();
+    if let Some(credits) = &metadata.credits {
+        let credits = credits
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .collect::<Vec<_>>();
+        if !credits.is_empty() {
+            about.add_credit_section("Credits", &credits);
+        }
+    }
+    // the dialog isn't run, so close it ourselves
+    about.connect_response(|about, _| unsafe { about.destroy() });
+
+    about
+}
+
+impl From<LicenseKind> for gtk::License {
+    fn from(kind: LicenseKind) -> Self {
+        match kind {
+            LicenseKind::Gpl20 => gtk::License::Gpl20,
+            LicenseKind::Gpl30 => gtk::License::Gpl30,
+            LicenseKind::Lgpl21 => gtk::License::Lgpl21,
+            LicenseKind::Lgpl30 => gtk::License::Lgpl30,
+            LicenseKind::Bsd => gtk::License::Bsd,
+            LicenseKind::MitX11 => gtk::License::MitX11,
+            LicenseKind::Artistic => gtk::License::Artistic,
+            LicenseKind::Gpl20Only => gtk::License::Gpl20Only,
+            LicenseKind::Gpl30Only => gtk::License::Gpl30Only,
+            LicenseKind::Lgpl21Only => gtk::License::Lgpl21Only,
+            LicenseKind::Lgpl30Only => gtk::License::Lgpl30Only,
+            LicenseKind::Agpl30 => gtk::License::Agpl30,
+            LicenseKind::Agpl30Only => gtk::License::Agpl30Only,
+            LicenseKind::Bsd3 => gtk::License::Bsd3,
+            LicenseKind::Apache20 => gtk::License::Apache20,
+            LicenseKind::Mpl20 => gtk::License::Mpl20,
+        }
+    }
+}
+
+type AboutDialogHandler = Box<dyn Fn(&AboutMetadata) -> gtk::Window>;
+
+thread_local! {
+    /// Set by [`AboutMetadata::set_gtk_dialog_handler`] to replace [`about_dialog`].
+    static ABOUT_DIALOG_HANDLER: RefCell<Option<AboutDialogHandler>> = RefCell::new(None);
+}
+
+pub(crate) fn set_about_dialog_handler(handler: Option<AboutDialogHandler>) {
+    ABOUT_DIALOG_HANDLER.with(|h| *h.borrow_mut() = handler);
+}
+
+/// Performs the clipboard action of a `Copy`, `Cut`, `Paste` or `SelectAll` item
+/// on the focused widget of `window`, through the keybinding signals
+/// of GTK widgets, which works on both X11 and Wayland.
+///
+/// Returns `false` if there is no focused widget or it doesn't support the action.
+fn emit_clipboard_signal(window: Option<&gtk::Window>, item_type: &PredefinedMenuItemType) -> bool {
+    let Some(widget) = window.and_then(|w| w.focused_widget()) else {
+        return false;
+    };
+
+    let signal = match item_type {
+        PredefinedMenuItemType::Copy => "copy-clipboard",
+        PredefinedMenuItemType::Cut => "cut-clipboard",
+        PredefinedMenuItemType::Paste => "paste-clipboard",
+        PredefinedMenuItemType::SelectAll => return select_all(&widget),
+        _ => return false,
+    };
+
+    if glib::subclass::SignalId::lookup(signal, widget.type_()).is_none() {
+        return false;
+    }
+
+    widget.emit_by_name::<()>(signal, &[]);
+    true
+}
+
+/// Selects all the content of `widget`, returns `false` if it isn't selectable.
+///
+/// The `select-all` signals of GTK widgets don't share the same signature,
+/// so each widget type is handled through its own API instead.
+fn select_all(widget: &gtk::Widget) -> bool {
+    if let Some(editable) = widget.dynamic_cast_ref::<gtk::Editable>() {
+        editable.select_region(0, -1);
+    } else if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+        label.select_region(0, -1);
+    } else if let Some(text_view) = widget.downcast_ref::<gtk::TextView>() {
+        // `GtkTextView::select-all` takes whether to select or unselect
+        text_view.emit_by_name::<()>("select-all", &[&true]);
+    } else if let Some(tree_view) = widget.downcast_ref::<gtk::TreeView>() {
+        tree_view.selection().select_all();
+    } else if let Some(list_box) = widget.downcast_ref::<gtk::ListBox>() {
+        list_box.select_all();
+    } else if let Some(flow_box) = widget.downcast_ref::<gtk::FlowBox>() {
+        flow_box.select_all();
+    } else if let Some(icon_view) = widget.downcast_ref::<gtk::IconView>() {
+        icon_view.select_all();
+    } else {
+        return false;
+    }
+    true
+}
+
+/// Finds the toplevel window that owns `item`, following the widgets
+/// submenus are attached to, and falls back to the active window
+/// for context menus that are not attached to any widget.
+fn toplevel_window(item: &gtk::MenuItem) -> Option<gtk::Window> {
+    let mut widget = item.clone().upcast::<gtk::Widget>();
+    loop {
+        let next = match widget.downcast_ref::<gtk::Menu>() {
+            Some(menu) => menu.attach_widget(),
+            None => widget.parent(),
+        };
+        match next {
+            Some(next) => widget = next,
+            None => break,
+        }
+    }
+
+    widget
+        .downcast::<gtk::Window>()
+        .ok()
+        .filter(|w| w.window_type() == gtk::WindowType::Toplevel)
+        .or_else(active_window)
+}
+
+/// Returns the id `window` is known by in the maps of menus and items, see [`super::window_id::window_id`].
+fn window_id(window: &impl IsA<gtk::Window>) -> u32 {
+    unsafe { super::window_id::window_id(window.as_ref().as_ptr().cast()) }
+}
+
+fn active_window() -> Option<gtk::Window> {
+    gtk::Window::list_toplevels()
+        .into_iter()
+        .filter_map(|w| w.downcast::<gtk::Window>().ok())
+        .find(|w| w.is_active())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Menu, PredefinedMenuItem, Submenu};
+    use crossbeam_channel::Sender;
+    use once_cell::sync::Lazy;
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// A test to run on the GTK thread, and where to send whether it panicked.
+    type GtkTest = (Box<dyn FnOnce() + Send>, Sender<std::thread::Result<()>>);
+
+    /// The sender of the tests to run on the GTK thread, or `None` if GTK couldn't be initialized.
+    static GTK_THREAD: Lazy<Option<Sender<GtkTest>>> = Lazy::new(|| {
+        let (ready_tx, ready_rx) = crossbeam_channel::bounded(1);
+        let (tx, rx) = crossbeam_channel::unbounded::<GtkTest>();
+        std::thread::spawn(move || {
+            let initialized = gtk::init().is_ok();
+            let _ = ready_tx.send(initialized);
+            if initialized {
+                for (test, result) in rx {
+                    let _ = result.send(panic::catch_unwind(AssertUnwindSafe(test)));
+                }
+            }
+        });
+        ready_rx.recv().unwrap_or(false).then_some(tx)
+    });
+
+    /// Runs `test` on the thread GTK was initialized on, as GTK can only be used from that thread
+    /// while every test runs on its own, or skips it if there is no display to initialize GTK on.
+    pub(super) fn with_gtk(test: impl FnOnce() + Send + 'static) {
+        let Some(gtk_thread) = &*GTK_THREAD else {
+            eprintln!("skipped, there is no display to run GTK on");
+            return;
+        };
+
+        let (result_tx, result_rx) = crossbeam_channel::bounded(1);
+        gtk_thread.send((Box::new(test), result_tx)).unwrap();
+        if let Err(panic) = result_rx.recv().unwrap() {
+            panic::resume_unwind(panic);
+        }
+    }
+
+    fn flush_events() {
+        while gtk::events_pending() {
+            gtk::main_iteration_do(false);
+        }
+    }
+
+    /// Runs the main loop until `done` returns `true`, for at most two seconds.
+    fn wait_for(done: impl Fn() -> bool) {
+        let start = std::time::Instant::now();
+        while !done() && start.elapsed() < Duration::from_secs(2) {
+            flush_events();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn gtk_items(item: &PredefinedMenuItem) -> Vec<gtk::MenuItem> {
+        let child = item.inner.borrow();
+        let items = child.gtk_menu_items.borrow();
+        items.values().flatten().cloned().collect()
+    }
+
+    #[test]
+    fn predefined_items_are_created_and_activate() {
+        with_gtk(|| {
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            let vbox = gtk::Box::new(Orientation::Vertical, 0);
+            window.add(&vbox);
+
+            let undo = PredefinedMenuItem::undo(None);
+            let redo = PredefinedMenuItem::redo(None);
+            let minimize = PredefinedMenuItem::minimize(None);
+            let maximize = PredefinedMenuItem::maximize(None);
+            let fullscreen = PredefinedMenuItem::fullscreen(None);
+            let hide = PredefinedMenuItem::hide(None);
+            let close_window = PredefinedMenuItem::close_window(None);
+            let quit = PredefinedMenuItem::quit(None);
+            let items = [
+                &undo,
+                &redo,
+                &minimize,
+                &maximize,
+                &fullscreen,
+                &hide,
+                &close_window,
+                &quit,
+            ];
+
+            let submenu =
+                Submenu::with_items("Window", true, &items.map(|i| i as &dyn crate::IsMenuItem))
+                    .unwrap();
+            let menu = Menu::with_items(&[&submenu]).unwrap();
+            menu.init_for_gtk_window(&window, Some(&vbox)).unwrap();
+            window.show_all();
+            flush_events();
+
+            for item in &items[2..] {
+                let gtk_items = gtk_items(item);
+                assert_eq!(gtk_items.len(), 1, "{:?}", item.text());
+                assert_eq!(toplevel_window(&gtk_items[0]).as_ref(), Some(&window));
+            }
+
+            // undo and redo only have keys to send through libxdo
+            for item in [&undo, &redo] {
+                assert_eq!(gtk_items(item).len(), usize::from(cfg!(feature = "libxdo")));
+            }
+
+            #[cfg(feature = "libxdo")]
+            {
+                for (item, item_type) in [
+                    (&undo, PredefinedMenuItemType::Undo),
+                    (&redo, PredefinedMenuItemType::Redo),
+                ] {
+                    let label = gtk_items(item)[0]
+                        .child()
+                        .unwrap()
+                        .downcast::<gtk::AccelLabel>()
+                        .unwrap();
+                    let (mods, key) = parse_accelerator(&item_type.accelerator().unwrap()).unwrap();
+                    assert_eq!(label.accel(), (key, mods));
+                }
+
+                // undo and redo send their keys to whichever window is focused,
+                // so they are only activated when it's this one
+                let entry = gtk::Entry::new();
+                vbox.add(&entry);
+                entry.show();
+                window.present();
+                flush_events();
+                entry.grab_focus();
+                if window.is_active() {
+                    let keys = Rc::new(RefCell::new(Vec::new()));
+                    let keys_c = keys.clone();
+                    entry.connect_key_press_event(move |_, event| {
+                        keys_c.borrow_mut().push(event.keyval());
+                        glib::Propagation::Stop
+                    });
+
+                    for item in [&undo, &redo] {
+                        gtk_items(item)[0].activate();
+                    }
+                    wait_for(|| keys.borrow().len() >= 2);
+                    assert!(keys.borrow().contains(&gdk::keys::constants::z));
+                    assert!(keys.borrow().contains(&gdk::keys::constants::y));
+                }
+                assert!(!MenuEvent::receiver()
+                    .try_iter()
+                    .any(|e| e.id() == undo.id() || e.id() == redo.id()));
+            }
+
+            // window states are applied by the window manager, which lists the
+            // windows it manages in the window stack
+            let has_window_manager =
+                gdk::Screen::default().is_some_and(|screen| !screen.window_stack().is_empty());
+            if has_window_manager {
+                let has_state = |state| window.window().is_some_and(|w| w.state().contains(state));
+                gtk_items(&fullscreen)[0].activate();
+                wait_for(|| has_state(gdk::WindowState::FULLSCREEN));
+                assert!(has_state(gdk::WindowState::FULLSCREEN));
+                gtk_items(&fullscreen)[0].activate();
+                wait_for(|| !has_state(gdk::WindowState::FULLSCREEN));
+                assert!(!has_state(gdk::WindowState::FULLSCREEN));
+
+                gtk_items(&maximize)[0].activate();
+                wait_for(|| window.is_maximized());
+                assert!(window.is_maximized());
+                gtk_items(&maximize)[0].activate();
+                wait_for(|| !window.is_maximized());
+                assert!(!window.is_maximized());
+
+                gtk_items(&minimize)[0].activate();
+                wait_for(|| has_state(gdk::WindowState::ICONIFIED));
+                assert!(has_state(gdk::WindowState::ICONIFIED));
+                window.deiconify();
+                flush_events();
+            }
+
+            gtk_items(&hide)[0].activate();
+            flush_events();
+            assert!(!window.is_visible());
+            window.show_all();
+
+            gtk_items(&quit)[0].activate();
+            flush_events();
+            assert!(MenuEvent::receiver()
+                .try_iter()
+                .any(|e| e.id() == quit.id()));
+
+            let destroyed = Rc::new(AtomicBool::new(false));
+            let destroyed_c = destroyed.clone();
+            window.connect_destroy(move |_| destroyed_c.store(true, Ordering::Relaxed));
+            gtk_items(&close_window)[0].activate();
+            flush_events();
+            assert!(destroyed.load(Ordering::Relaxed));
+        });
+    }
+
+    #[test]
+    fn select_all_works_on_every_selectable_widget() {
+        with_gtk(|| {
+            let text_view = gtk::TextView::new();
+            text_view.buffer().unwrap().set_text("text");
+            assert!(select_all(text_view.upcast_ref()));
+            assert!(text_view.buffer().unwrap().has_selection());
+
+            let store = gtk::ListStore::new(&[glib::Type::STRING]);
+            store.insert_with_values(None, &[(0, &"a")]);
+            store.insert_with_values(None, &[(0, &"b")]);
+            let tree_view = gtk::TreeView::with_model(&store);
+            tree_view.selection().set_mode(gtk::SelectionMode::Multiple);
+            assert!(select_all(tree_view.upcast_ref()));
+            assert_eq!(tree_view.selection().count_selected_rows(), 2);
+
+            let list_box = gtk::ListBox::new();
+            list_box.set_selection_mode(gtk::SelectionMode::Multiple);
+            list_box.add(&gtk::Label::new(Some("a")));
+            list_box.add(&gtk::Label::new(Some("b")));
+            assert!(select_all(list_box.upcast_ref()));
+            assert_eq!(list_box.selected_rows().len(), 2);
+
+            assert!(select_all(gtk::FlowBox::new().upcast_ref()));
+            assert!(select_all(gtk::IconView::new().upcast_ref()));
+            assert!(!select_all(gtk::Button::new().upcast_ref()));
+        });
+    }
+
+    #[test]
+    fn menubar_is_placed_by_the_callback() {
+        with_gtk(|| {
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            let grid = gtk::Grid::new();
+            window.add(&grid);
+
+            let menu = Menu::with_items(&[&Submenu::new("File", true)]).unwrap();
+            menu.init_for_gtk_container(&window, |menu_bar| {
+                assert!(menu_bar.parent().is_none());
+                grid.attach(menu_bar, 1, 0, 1, 1);
+            })
+            .unwrap();
+            assert!(matches!(
+                menu.init_for_gtk_container(&window, |_| unreachable!()),
+                Err(crate::Error::AlreadyInitialized)
+            ));
+
+            let menu_bar = menu.clone().gtk_menubar_for_gtk_window(&window).unwrap();
+            assert_eq!(grid.child_at(1, 0), Some(menu_bar.clone().upcast()));
+            assert_eq!(menu_bar.children().len(), 1);
+
+            menu.remove_for_gtk_window(&window).unwrap();
+            assert!(grid.child_at(1, 0).is_none());
+        });
+    }
+
+    #[test]
+    fn destroyed_windows_are_forgotten() {
+        with_gtk(|| {
+            let item = crate::MenuItem::new("Item", true, None);
+            let menu = Menu::with_items(&[&item]).unwrap();
+
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            menu.init_for_gtk_window(&window, None::<&gtk::Box>)
+                .unwrap();
+            let other = gtk::Window::new(gtk::WindowType::Toplevel);
+            menu.init_for_gtk_window(&other, None::<&gtk::Box>).unwrap();
+            assert_ne!(window_id(&window), window_id(&other));
+            assert_eq!(item.inner.borrow().gtk_menu_items.borrow().len(), 2);
+
+            assert_eq!(gtk::accel_groups_from_object(&window).len(), 1);
+            unsafe { window.destroy() };
+            flush_events();
+            assert!(gtk::accel_groups_from_object(&window).is_empty());
+            assert!(menu.clone().gtk_menubar_for_gtk_window(&window).is_none());
+            assert!(menu.clone().gtk_menubar_for_gtk_window(&other).is_some());
+            assert_eq!(item.inner.borrow().gtk_menu_items.borrow().len(), 1);
+
+            // a new window never reuses the id of a destroyed one
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            assert!(menu.is_visible_on_gtk_window(&other));
+            assert!(!menu.is_visible_on_gtk_window(&window));
+            menu.init_for_gtk_window(&window, None::<&gtk::Box>)
+                .unwrap();
+
+            menu.remove_for_gtk_window(&other).unwrap();
+            assert_eq!(item.inner.borrow().gtk_menu_items.borrow().len(), 1);
+        });
+    }
+
+    #[test]
+    fn moved_items_keep_their_widgets() {
+        with_gtk(|| {
+            let open = crate::MenuItem::with_id("open", "Open", true, None);
+            let save = crate::MenuItem::with_id("save", "Save", true, None);
+            let file = Submenu::with_id_and_items("file", "File", true, &[&open, &save]).unwrap();
+            let edit = Submenu::with_id("edit", "Edit", true);
+            let menu = Menu::with_items(&[&file, &edit]).unwrap();
+
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            menu.init_for_gtk_window(&window, None::<&gtk::Box>)
+                .unwrap();
+            let menu_bar = menu.clone().gtk_menubar_for_gtk_window(&window).unwrap();
+            let gtk_save = save
+                .inner
+                .borrow()
+                .gtk_menu_items
+                .borrow()
+                .values()
+                .flatten()
+                .next()
+                .cloned()
+                .unwrap();
+            let gtk_menu = gtk_save.parent().unwrap();
+            let gtk_edit = edit
+                .inner
+                .borrow()
+                .gtk_menu_items
+                .borrow()
+                .values()
+                .flatten()
+                .next()
+                .cloned()
+                .unwrap();
+
+            file.move_item(&save, 0).unwrap();
+            assert_eq!(file.items()[0].id(), save.id());
+            assert_eq!(
+                gtk_menu.downcast_ref::<Container>().unwrap().children()[0],
+                gtk_save.clone().upcast::<gtk::Widget>()
+            );
+
+            menu.move_item(&edit, 0).unwrap();
+            assert_eq!(menu_bar.children()[0], gtk_edit.upcast::<gtk::Widget>());
+
+            file.move_item_to(&save, &edit, 0).unwrap();
+            assert!(file.items().iter().all(|i| i.id() != save.id()));
+            assert_eq!(edit.items()[0].id(), save.id());
+            assert_eq!(save.inner.borrow().gtk_menu_items.borrow().len(), 1);
+
+            assert!(matches!(
+                menu.move_item_to(&file, &file, 0),
+                Err(crate::Error::SubmenuIntoItself)
+            ));
+            assert!(matches!(
+                file.move_item(&save, 0),
+                Err(crate::Error::NotAChildOfThisMenu)
+            ));
+        });
+    }
+
+    #[test]
+    fn moved_items_skip_unsupported_items_and_keep_repeated_ones() {
+        with_gtk(|| {
+            let labels = |menu: &gtk::Container| {
+                menu.children()
+                    .into_iter()
+                    .map(|w| w.downcast::<gtk::MenuItem>().unwrap().label().unwrap())
+                    .collect::<Vec<_>>()
+            };
+
+            let one = crate::MenuItem::new("One", true, None);
+            let two = crate::MenuItem::new("Two", true, None);
+            let recent = Submenu::with_items(
+                "Recent",
+                true,
+                &[&PredefinedMenuItem::services(None), &one, &two, &one],
+            )
+            .unwrap();
+            let menu = Menu::with_items(&[&recent]).unwrap();
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            menu.init_for_gtk_window(&window, None::<&gtk::Box>)
+                .unwrap();
+            let gtk_menu = two
+                .inner
+                .borrow()
+                .gtk_menu_items
+                .borrow()
+                .values()
+                .flatten()
+                .next()
+                .unwrap()
+                .parent()
+                .unwrap();
+            let gtk_menu = gtk_menu.downcast_ref::<Container>().unwrap();
+            assert_eq!(labels(gtk_menu), ["One", "Two", "One"]);
+
+            // the services item has no widget, so `Two` is the first widget
+            recent.move_item(&two, 1).unwrap();
+            assert_eq!(labels(gtk_menu), ["Two", "One", "One"]);
+
+            recent.move_item(&two, 3).unwrap();
+            assert_eq!(labels(gtk_menu), ["One", "One", "Two"]);
+            assert_eq!(
+                one.inner
+                    .borrow()
+                    .gtk_menu_items
+                    .borrow()
+                    .values()
+                    .flatten()
+                    .count(),
+                2
+            );
+        });
+    }
+
+    #[test]
+    fn batched_items_are_created_when_the_batch_ends() {
+        with_gtk(|| {
+            let item_count =
+                |item: &crate::MenuItem| item.inner.borrow().gtk_menu_items.borrow().len();
+            let labels = |menu: &gtk::Container| {
+                menu.children()
+                    .into_iter()
+                    .map(|w| w.downcast::<gtk::MenuItem>().unwrap().label().unwrap())
+                    .collect::<Vec<_>>()
+            };
+
+            let first = crate::MenuItem::new("First", true, None);
+            let recent = Submenu::with_items("Recent", true, &[&first]).unwrap();
+            let menu = Menu::with_items(&[&recent]).unwrap();
+            let window = gtk::Window::new(gtk::WindowType::Toplevel);
+            menu.init_for_gtk_window(&window, None::<&gtk::Box>)
+                .unwrap();
+            let gtk_menu = first
+                .inner
+                .borrow()
+                .gtk_menu_items
+                .borrow()
+                .values()
+                .flatten()
+                .next()
+                .unwrap()
+                .parent()
+                .unwrap();
+            let gtk_menu = gtk_menu.downcast_ref::<Container>().unwrap();
+
+            let second = crate::MenuItem::new("Second", true, None);
+            let zeroth = crate::MenuItem::new("Zeroth", true, None);
+            menu.batch(|_| {
+                recent.append(&second).unwrap();
+                recent.prepend(&zeroth).unwrap();
+                assert_eq!(item_count(&second), 0);
+                assert_eq!(item_count(&zeroth), 0);
+            })
+            .unwrap();
+            assert_eq!(labels(gtk_menu), ["Zeroth", "First", "Second"]);
+
+            let other = crate::MenuItem::new("Other", true, None);
+            recent.replace_items(&[&other, &first]).unwrap();
+            assert_eq!(labels(gtk_menu), ["Other", "First"]);
+            assert_eq!(item_count(&second), 0);
+
+            // the batch still ends when `f` panics
+            let last = crate::MenuItem::new("Last", true, None);
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                menu.batch(|_| {
+                    recent.append(&last).unwrap();
+                    panic!("failed in the batch");
+                })
+            }));
+            assert!(res.is_err());
+            assert_eq!(labels(gtk_menu), ["Other", "First", "Last"]);
+            recent.remove(&last).unwrap();
+            recent.append(&second).unwrap();
+            assert_eq!(item_count(&second), 1);
+        });
+    }
+
+    #[test]
+    fn tooltips_are_shown_and_sent_on_highlight() {
+        with_gtk(|| {
+            let item = crate::MenuItemBuilder::new()
+                .text("Open")
+                .enabled(true)
+                .tooltip("Open a file")
+                .build();
+            let menu = Menu::with_items(&[&item]).unwrap();
+            let gtk_menu = crate::ContextMenu::gtk_context_menu(&menu);
+            let gtk_item = gtk_menu.children()[0].clone();
+            assert_eq!(gtk_item.tooltip_text().as_deref(), Some("Open a file"));
+
+            item.set_tooltip(Some("Open an existing file"));
+            assert_eq!(item.tooltip().as_deref(), Some("Open an existing file"));
+            assert_eq!(
+                gtk_item.tooltip_text().as_deref(),
+                Some("Open an existing file")
+            );
+
+            gtk_item.downcast::<gtk::MenuItem>().unwrap().select();
+            let event = crate::MenuHighlightEvent::receiver()
+                .try_iter()
+                .find(|e| e.id() == item.id())
+                .unwrap();
+            assert_eq!(event.tooltip(), Some("Open an existing file"));
+        });
+    }
+
+    #[test]
+    fn shared_items_update_every_widget() {
+        with_gtk(|| {
+            let item = crate::CheckMenuItem::new("Wrap", true, false, None);
+            let file = crate::Submenu::with_items("File", true, &[&item]).unwrap();
+            let menu = Menu::with_items(&[&file, &item]).unwrap();
+            let context_menu = Menu::with_items(&[&item]).unwrap();
+            let widgets = || {
+                [
+                    crate::ContextMenu::gtk_context_menu(&menu).children()[1].clone(),
+                    crate::ContextMenu::gtk_context_menu(&context_menu).children()[0].clone(),
+                    crate::ContextMenu::gtk_context_menu(&file).children()[0].clone(),
+                ]
+                .map(|w| w.downcast::<gtk::CheckMenuItem>().unwrap())
+            };
+
+            item.set_text("Word Wrap");
+            item.set_enabled(false);
+            item.set_checked(true);
+            for widget in widgets() {
+                assert_eq!(widget.label().as_deref(), Some("Word Wrap"));
+                assert!(!widget.is_sensitive());
+                assert!(widget.is_active());
+            }
+
+            item.remove_from_all();
+            assert!(item.parents().is_empty());
+            assert_eq!(
+                crate::ContextMenu::gtk_context_menu(&menu).children().len(),
+                1
+            );
+            assert!(crate::ContextMenu::gtk_context_menu(&context_menu)
+                .children()
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn deep_clones_keep_icon_animations() {
+        with_gtk(|| {
+            let frame = |c| Icon::from_rgba(vec![c; 4], 1, 1).unwrap();
+            let item = crate::IconMenuItem::new("Sync", true, None, None);
+            item.set_animated_icon(vec![frame(0), frame(255)], Duration::from_millis(100));
+            let file = Submenu::with_items("File", true, &[&item]).unwrap();
+
+            let copy = file.deep_clone(|_| None).unwrap();
+            let copy = copy.items()[0].as_icon_menuitem().unwrap().clone();
+            let copy = copy.inner.borrow();
+            let animation = copy.icon_animation.as_ref().unwrap().borrow();
+            assert_eq!(animation.frames.len(), 2);
+            assert_eq!(animation.interval, Duration::from_millis(100));
+            assert_eq!(animation.frame, 0);
+        });
+    }
+}
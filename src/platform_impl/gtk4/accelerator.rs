@@ -0,0 +1,174 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use gtk4::gdk;
+use keyboard_types::{Code, Modifiers};
+
+use crate::accelerator::{Accelerator, AcceleratorParseError};
+
+pub fn to_gtk_mnemonic<S: AsRef<str>>(string: S) -> String {
+    string
+        .as_ref()
+        .replace("&&", "[~~]")
+        .replace('&', "_")
+        .replace("[~~]", "&&")
+        .replace("[~~]", "&")
+}
+
+pub fn parse_accelerator(
+    accelerator: &Accelerator,
+) -> Result<(gdk::ModifierType, gdk::Key), AcceleratorParseError> {
+    let key = match &accelerator.key {
+        Code::KeyA => gdk::Key::a,
+        Code::KeyB => gdk::Key::b,
+        Code::KeyC => gdk::Key::c,
+        Code::KeyD => gdk::Key::d,
+        Code::KeyE => gdk::Key::e,
+        Code::KeyF => gdk::Key::f,
+        Code::KeyG => gdk::Key::g,
+        Code::KeyH => gdk::Key::h,
+        Code::KeyI => gdk::Key::i,
+        Code::KeyJ => gdk::Key::j,
+        Code::KeyK => gdk::Key::k,
+        Code::KeyL => gdk::Key::l,
+        Code::KeyM => gdk::Key::m,
+        Code::KeyN => gdk::Key::n,
+        Code::KeyO => gdk::Key::o,
+        Code::KeyP => gdk::Key::p,
+        Code::KeyQ => gdk::Key::q,
+        Code::KeyR => gdk::Key::r,
+        Code::KeyS => gdk::Key::s,
+        Code::KeyT => gdk::Key::t,
+        Code::KeyU => gdk::Key::u,
+        Code::KeyV => gdk::Key::v,
+        Code::KeyW => gdk::Key::w,
+        Code::KeyX => gdk::Key::x,
+        Code::KeyY => gdk::Key::y,
+        Code::KeyZ => gdk::Key::z,
+        Code::Digit0 => gdk::Key::_0,
+        Code::Digit1 => gdk::Key::_1,
+        Code::Digit2 => gdk::Key::_2,
+        Code::Digit3 => gdk::Key::_3,
+        Code::Digit4 => gdk::Key::_4,
+        Code::Digit5 => gdk::Key::_5,
+        Code::Digit6 => gdk::Key::_6,
+        Code::Digit7 => gdk::Key::_7,
+        Code::Digit8 => gdk::Key::_8,
+        Code::Digit9 => gdk::Key::_9,
+        Code::Comma => gdk::Key::comma,
+        Code::Minus => gdk::Key::minus,
+        Code::Period => gdk::Key::period,
+        Code::Space => gdk::Key::space,
+        Code::Equal => gdk::Key::equal,
+        Code::Semicolon => gdk::Key::semicolon,
+        Code::Slash => gdk::Key::slash,
+        Code::Backslash => gdk::Key::backslash,
+        Code::Quote => gdk::Key::apostrophe,
+        Code::Backquote => gdk::Key::grave,
+        Code::BracketLeft => gdk::Key::bracketleft,
+        Code::BracketRight => gdk::Key::bracketright,
+        key => {
+            if let Some(gdk_key) = key_to_raw_key(key) {
+                gdk_key
+            } else {
+                return Err(AcceleratorParseError::UnsupportedKey(key.to_string()));
+            }
+        }
+    };
+
+    Ok((modifiers_to_gdk_modifier_type(accelerator.mods), key))
+}
+
+fn modifiers_to_gdk_modifier_type(modifiers: Modifiers) -> gdk::ModifierType {
+    let mut result = gdk::ModifierType::empty();
+
+    result.set(
+        gdk::ModifierType::ALT_MASK,
+        modifiers.contains(Modifiers::ALT),
+    );
+    result.set(
+        gdk::ModifierType::CONTROL_MASK,
+        modifiers.contains(Modifiers::CONTROL),
+    );
+    result.set(
+        gdk::ModifierType::SHIFT_MASK,
+        modifiers.contains(Modifiers::SHIFT),
+    );
+    result.set(
+        gdk::ModifierType::SUPER_MASK,
+        modifiers.contains(Modifiers::SUPER),
+    );
+
+    result
+}
+
+fn key_to_raw_key(src: &Code) -> Option<gdk::Key> {
+    use gdk::Key;
+    Some(match src {
+        Code::Escape => Key::Escape,
+        Code::Backspace => Key::BackSpace,
+
+        Code::Tab => Key::Tab,
+        Code::Enter => Key::Return,
+
+        Code::ControlLeft => Key::Control_L,
+        Code::AltLeft => Key::Alt_L,
+        Code::ShiftLeft => Key::Shift_L,
+        Code::MetaLeft => Key::Super_L,
+
+        Code::ControlRight => Key::Control_R,
+        Code::AltRight => Key::Alt_R,
+        Code::ShiftRight => Key::Shift_R,
+        Code::MetaRight => Key::Super_R,
+
+        Code::CapsLock => Key::Caps_Lock,
+        Code::F1 => Key::F1,
+        Code::F2 => Key::F2,
+        Code::F3 => Key::F3,
+        Code::F4 => Key::F4,
+        Code::F5 => Key::F5,
+        Code::F6 => Key::F6,
+        Code::F7 => Key::F7,
+        Code::F8 => Key::F8,
+        Code::F9 => Key::F9,
+        Code::F10 => Key::F10,
+        Code::F11 => Key::F11,
+        Code::F12 => Key::F12,
+        Code::F13 => Key::F13,
+        Code::F14 => Key::F14,
+        Code::F15 => Key::F15,
+        Code::F16 => Key::F16,
+        Code::F17 => Key::F17,
+        Code::F18 => Key::F18,
+        Code::F19 => Key::F19,
+        Code::F20 => Key::F20,
+        Code::F21 => Key::F21,
+        Code::F22 => Key::F22,
+        Code::F23 => Key::F23,
+        Code::F24 => Key::F24,
+
+        Code::PrintScreen => Key::Print,
+        Code::ScrollLock => Key::Scroll_Lock,
+        // Pause/Break not audio.
+        Code::Pause => Key::Pause,
+
+        Code::Insert => Key::Insert,
+        Code::Delete => Key::Delete,
+        Code::Home => Key::Home,
+        Code::End => Key::End,
+        Code::PageUp => Key::Page_Up,
+        Code::PageDown => Key::Page_Down,
+
+        Code::NumLock => Key::Num_Lock,
+
+        Code::ArrowUp => Key::Up,
+        Code::ArrowDown => Key::Down,
+        Code::ArrowLeft => Key::Left,
+        Code::ArrowRight => Key::Right,
+
+        Code::ContextMenu => Key::Menu,
+        Code::WakeUp => Key::WakeUp,
+        _ => return None,
+    })
+}
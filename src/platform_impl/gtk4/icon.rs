@@ -0,0 +1,150 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use gtk4::{gdk, glib, prelude::*};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
+
+use crate::{
+    icon::{IconCacheStats, RgbaIcon},
+    platform_impl::PlatformIcon,
+};
+
+/// Returns a texture of `icon`, shared with any other icon
+/// that has the same content, see [`TEXTURE_CACHE`].
+///
+/// Unlike GTK3 menu items, GTK4 menus scale icons themselves.
+pub(crate) fn to_cached_texture(icon: &PlatformIcon) -> gdk::Texture {
+    let (rgba, content_hash) = icon.shared_rgba();
+    TEXTURE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(texture) = cache.get(content_hash, rgba) {
+            return texture;
+        }
+
+        let texture = texture_from_rgba_icon(rgba);
+        cache.insert(content_hash, rgba, texture.clone());
+        texture
+    })
+}
+
+fn texture_from_rgba_icon(icon: &RgbaIcon) -> gdk::Texture {
+    gdk::MemoryTexture::new(
+        icon.width as i32,
+        icon.height as i32,
+        gdk::MemoryFormat::R8g8b8a8,
+        &glib::Bytes::from(&icon.rgba[..]),
+        icon.width as usize * 4,
+    )
+    .into()
+}
+
+thread_local! {
+    /// Textures created for icon menu items, shared by every menu model
+    /// that displays an icon with the same content.
+    ///
+    /// Entries only hold weak references to the icon data they were used for
+    /// and are evicted once every icon with their content is dropped.
+    static TEXTURE_CACHE: RefCell<TextureCache> = RefCell::new(TextureCache::default());
+}
+
+struct CacheEntry {
+    // every distinct icon data with this content that hit the entry
+    icons: Vec<Weak<RgbaIcon>>,
+    texture: gdk::Texture,
+}
+
+impl CacheEntry {
+    /// Whether `icon` has the content of this entry, in which case it
+    /// also keeps the entry alive from now on.
+    fn matches(&mut self, icon: &Arc<RgbaIcon>) -> bool {
+        if self
+            .icons
+            .iter()
+            .any(|cached| cached.as_ptr() == Arc::as_ptr(icon))
+        {
+            return true;
+        }
+
+        self.icons.retain(|cached| cached.strong_count() > 0);
+        let matches = self
+            .icons
+            .iter()
+            .find_map(Weak::upgrade)
+            .is_some_and(|cached| *cached == **icon);
+        if matches {
+            self.icons.push(Arc::downgrade(icon));
+        }
+        matches
+    }
+
+    fn is_alive(&self) -> bool {
+        self.icons.iter().any(|icon| icon.strong_count() > 0)
+    }
+}
+
+#[derive(Default)]
+struct TextureCache {
+    // a bucket may hold more than one entry in case of hash collisions
+    entries: HashMap<u64, Vec<CacheEntry>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl TextureCache {
+    fn get(&mut self, key: u64, icon: &Arc<RgbaIcon>) -> Option<gdk::Texture> {
+        let texture = self.entries.get_mut(&key).and_then(|bucket| {
+            bucket
+                .iter_mut()
+                .find_map(|entry| entry.matches(icon).then(|| entry.texture.clone()))
+        });
+
+        if texture.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        texture
+    }
+
+    fn insert(&mut self, key: u64, icon: &Arc<RgbaIcon>, texture: gdk::Texture) {
+        self.evict_dropped();
+        self.entries.entry(key).or_default().push(CacheEntry {
+            icons: vec![Arc::downgrade(icon)],
+            texture,
+        });
+    }
+
+    fn evict_dropped(&mut self) {
+        self.entries.retain(|_, bucket| {
+            bucket.retain(CacheEntry::is_alive);
+            !bucket.is_empty()
+        });
+    }
+
+    fn stats(&mut self) -> IconCacheStats {
+        self.evict_dropped();
+        let textures = self.entries.values().flatten().map(|e| &e.texture);
+        IconCacheStats {
+            entries: textures.clone().count(),
+            bytes: textures
+                .map(|t| t.width() as usize * t.height() as usize * 4)
+                .sum(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+pub(crate) fn icon_cache_stats() -> IconCacheStats {
+    TEXTURE_CACHE.with(|cache| cache.borrow_mut().stats())
+}
+
+pub(crate) fn clear_icon_cache() {
+    TEXTURE_CACHE.with(|cache| *cache.borrow_mut() = TextureCache::default());
+}
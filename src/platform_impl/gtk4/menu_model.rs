@@ -0,0 +1,345 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{self, Write},
+    rc::{Rc, Weak},
+};
+
+use gtk4::{gio, glib, prelude::*};
+
+use super::{
+    accelerator::{parse_accelerator, to_gtk_mnemonic},
+    activate_child,
+    icon::to_cached_texture,
+};
+use crate::{
+    items::PredefinedMenuItemType,
+    platform_impl::{
+        observer::{register_observer, MenuObserver},
+        Menu, MenuChild,
+    },
+    MenuId, MenuItemType,
+};
+
+/// The name the action group of a [`MenuModel`] is inserted with.
+pub(crate) const ACTION_GROUP_NAME: &str = "muda";
+
+/// The menu or submenu a [`MenuModel`] mirrors.
+pub(crate) enum ModelRoot {
+    Menu(Weak<RefCell<Menu>>),
+    Submenu(Weak<RefCell<MenuChild>>),
+}
+
+impl ModelRoot {
+    fn children(&self) -> Option<Vec<Rc<RefCell<MenuChild>>>> {
+        match self {
+            ModelRoot::Menu(menu) => menu.upgrade().map(|m| m.borrow().children().to_vec()),
+            ModelRoot::Submenu(submenu) => {
+                submenu.upgrade().and_then(|s| s.borrow().children.clone())
+            }
+        }
+    }
+}
+
+/// A [`gio::Menu`], the [`gio::SimpleActionGroup`] its items activate
+/// and the [`gtk4::Shortcut`]s of their accelerators, mirroring a [`Menu`] or a submenu.
+///
+/// A single model is shared by all the menubars and the context menu of its root,
+/// every item of the root keeps a weak reference to the model, changes to the structure,
+/// text, icon or accelerator of an item rebuild the model on the next idle,
+/// while enabled and checked states are applied to the actions directly.
+pub(crate) struct MenuModel {
+    this: Weak<MenuModel>,
+    root: ModelRoot,
+    pub(crate) model: gio::Menu,
+    pub(crate) actions: gio::SimpleActionGroup,
+    pub(crate) shortcuts: gio::ListStore,
+    rebuild_scheduled: Cell<bool>,
+}
+
+impl fmt::Debug for MenuModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MenuModel")
+            .field("model", &self.model)
+            .field("actions", &self.actions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MenuModel {
+    pub(crate) fn new(root: ModelRoot) -> Rc<Self> {
+        let model = Rc::new_cyclic(|this| Self {
+            this: this.clone(),
+            root,
+            model: gio::Menu::new(),
+            actions: gio::SimpleActionGroup::new(),
+            shortcuts: gio::ListStore::new::<gtk4::Shortcut>(),
+            rebuild_scheduled: Cell::new(false),
+        });
+        model.rebuild();
+        model
+    }
+
+    fn schedule_rebuild(&self) {
+        if self.rebuild_scheduled.replace(true) {
+            return;
+        }
+
+        let model = self.this.clone();
+        glib::idle_add_local_once(move || {
+            if let Some(model) = model.upgrade() {
+                model.rebuild();
+            }
+        });
+    }
+
+    fn rebuild(self: &Rc<Self>) {
+        self.rebuild_scheduled.set(false);
+
+        let Some(children) = self.root.children() else {
+            return;
+        };
+
+        for name in self.actions.list_actions() {
+            self.actions.remove_action(&name);
+        }
+        self.shortcuts.remove_all();
+        self.model.remove_all();
+        self.append_items(&self.model, &children);
+    }
+
+    /// Appends `children` to `menu`, separators split the items into sections.
+    fn append_items(self: &Rc<Self>, menu: &gio::Menu, children: &[Rc<RefCell<MenuChild>>]) {
+        let mut section = gio::Menu::new();
+        for child in children {
+            let child_ = child.borrow();
+            if !child_.is_supported() {
+                continue;
+            }
+
+            register_observer(child_.observers(), self.clone());
+
+            if matches!(
+                child_.predefined_item_type(),
+                Some(PredefinedMenuItemType::Separator)
+            ) {
+                if section.n_items() > 0 {
+                    menu.append_section(None, &section);
+                    section = gio::Menu::new();
+                }
+                continue;
+            }
+
+            let item = if child_.item_type() == MenuItemType::Submenu {
+                let submenu = gio::Menu::new();
+                self.append_items(&submenu, child_.children.as_ref().unwrap());
+                let item =
+                    gio::MenuItem::new_submenu(Some(&to_gtk_mnemonic(child_.text())), &submenu);
+
+                // submenus can only be disabled through an action
+                let name = action_name(child_.id());
+                let action = gio::SimpleAction::new_stateful(&name, None, &false.to_variant());
+                action.set_enabled(child_.is_enabled());
+                self.actions.add_action(&action);
+                item.set_attribute_value(
+                    "submenu-action",
+                    Some(&format!("{ACTION_GROUP_NAME}.{name}").to_variant()),
+                );
+
+                item
+            } else {
+                self.make_item(child, &child_)
+            };
+            section.append_item(&item);
+        }
+
+        if section.n_items() > 0 {
+            menu.append_section(None, &section);
+        }
+    }
+
+    fn make_item(&self, child: &Rc<RefCell<MenuChild>>, child_: &MenuChild) -> gio::MenuItem {
+        let name = action_name(child_.id());
+        let detailed_name = format!("{ACTION_GROUP_NAME}.{name}");
+        let item = gio::MenuItem::new(Some(&to_gtk_mnemonic(child_.text())), Some(&detailed_name));
+
+        let action = if child_.item_type() == MenuItemType::Check {
+            gio::SimpleAction::new_stateful(&name, None, &child_.is_checked().to_variant())
+        } else {
+            gio::SimpleAction::new(&name, None)
+        };
+        action.set_enabled(child_.is_enabled());
+
+        if let Some(icon) = child_.icon() {
+            item.set_icon(&to_cached_texture(&icon.inner));
+        }
+
+        let accel = child_
+            .accelerator()
+            .and_then(|accelerator| parse_accelerator(accelerator).ok());
+        if let Some((mods, key)) = accel {
+            item.set_attribute_value(
+                "accel",
+                Some(&gtk4::accelerator_name(key, mods).to_variant()),
+            );
+
+            // clipboard items only display their accelerator,
+            // so the keys still reach the focused widget
+            if !matches!(
+                child_.predefined_item_type(),
+                Some(
+                    PredefinedMenuItemType::Copy
+                        | PredefinedMenuItemType::Cut
+                        | PredefinedMenuItemType::Paste
+                        | PredefinedMenuItemType::SelectAll
+                        | PredefinedMenuItemType::Undo
+                        | PredefinedMenuItemType::Redo
+                )
+            ) {
+                let action = action.downgrade();
+                let callback = gtk4::CallbackAction::new(move |_, _| match action.upgrade() {
+                    Some(action) if action.is_enabled() => {
+                        action.activate(None);
+                        glib::Propagation::Stop
+                    }
+                    _ => glib::Propagation::Proceed,
+                });
+                self.shortcuts.append(&gtk4::Shortcut::new(
+                    Some(gtk4::KeyvalTrigger::new(key, mods)),
+                    Some(callback),
+                ));
+            }
+        }
+
+        let child = Rc::downgrade(child);
+        action.connect_activate(move |_, _| {
+            if let Some(child) = child.upgrade() {
+                activate_child(&child);
+            }
+        });
+        self.actions.add_action(&action);
+
+        item
+    }
+
+    fn action(&self, id: &MenuId) -> Option<gio::SimpleAction> {
+        self.actions
+            .lookup_action(&action_name(id))
+            .and_then(|a| a.downcast().ok())
+    }
+}
+
+impl MenuObserver for MenuModel {
+    fn layout_changed(&self) {
+        self.schedule_rebuild();
+    }
+
+    fn enabled_changed(&self, id: &MenuId, enabled: bool) {
+        if let Some(action) = self.action(id) {
+            action.set_enabled(enabled);
+        }
+    }
+
+    fn checked_changed(&self, id: &MenuId, checked: bool) {
+        if let Some(action) = self.action(id) {
+            action.set_state(&checked.to_variant());
+        }
+    }
+}
+
+/// Escapes `id` into a valid action name, which can only contain
+/// ASCII alphanumerics, `-` and `.`.
+fn action_name(id: &MenuId) -> String {
+    let mut name = String::with_capacity(id.0.len());
+    for b in id.0.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'-' {
+            name.push(b as char);
+        } else {
+            let _ = write!(name, ".{b:02x}");
+        }
+    }
+
+    if name.is_empty() {
+        name.push('.');
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckMenuItem, ContextMenu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+
+    fn flush_events() {
+        while glib::MainContext::default().iteration(false) {}
+    }
+
+    fn label(model: &impl IsA<gio::MenuModel>, index: i32) -> Option<String> {
+        model
+            .item_attribute_value(index, "label", None)
+            .and_then(|v| v.get::<String>())
+    }
+
+    #[test]
+    #[ignore = "needs a display, run with `cargo test --features gtk4 -- --ignored`"]
+    fn menubar_and_context_menu_share_the_model() {
+        gtk4::init().expect("no display to run on");
+
+        let item = MenuItem::with_id("item", "&Item", true, None);
+        let check = CheckMenuItem::with_id("check", "Check", true, false, None);
+        let submenu = Submenu::with_id_and_items(
+            "submenu",
+            "File",
+            true,
+            &[&item, &PredefinedMenuItem::separator(), &check],
+        )
+        .unwrap();
+        let menu = crate::Menu::with_items(&[&submenu]).unwrap();
+
+        let window = gtk4::Window::new();
+        let vbox = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+        window.set_child(Some(&vbox));
+        menu.init_for_gtk4_window(&window, Some(&vbox)).unwrap();
+        assert!(matches!(
+            menu.init_for_gtk4_window(&window, Some(&vbox)),
+            Err(crate::Error::AlreadyInitialized)
+        ));
+
+        let menu_bar = menu.gtk4_menubar_for_gtk4_window(&window).unwrap();
+        let (model, actions) = menu.to_gtk4_gio_menu_model();
+        assert_eq!(menu_bar.menu_model(), Some(model.clone().upcast()));
+        assert_eq!(
+            menu.gtk4_context_menu().menu_model(),
+            Some(model.clone().upcast())
+        );
+
+        // a single section holding the submenu, which has two sections
+        let section = model.item_link(0, "section").unwrap();
+        assert_eq!(label(&section, 0).as_deref(), Some("File"));
+        let file = section.item_link(0, "submenu").unwrap();
+        assert_eq!(file.n_items(), 2);
+
+        // enabled and checked states are applied right away
+        item.set_enabled(false);
+        assert!(!actions.is_action_enabled("item"));
+        actions.activate_action("check", None);
+        assert!(check.is_checked());
+        assert!(MenuEvent::receiver()
+            .try_iter()
+            .any(|e| e.id() == check.id()));
+
+        // structure changes rebuild the model on idle
+        submenu
+            .append(&MenuItem::with_id("new", "New", true, None))
+            .unwrap();
+        flush_events();
+        assert!(actions.has_action("new"));
+
+        menu.remove_for_gtk4_window(&window).unwrap();
+        assert!(menu_bar.parent().is_none());
+    }
+}
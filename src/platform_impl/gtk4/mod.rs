@@ -0,0 +1,642 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The GTK4 menubars and context menus of the `gtk4` feature, displaying
+//! the menus and items of the platform backend through [`MenuModel`]s.
+
+mod accelerator;
+mod icon;
+mod menu_model;
+
+pub(crate) use icon::{clear_icon_cache, icon_cache_stats};
+
+use crate::{
+    items::*,
+    platform_impl::{observer::register_observer, Menu, MenuChild},
+    AboutMetadata, ContextMenuAnchor, ContextMenuGravity, ContextMenuOptions, LicenseKind,
+    MenuEvent, MenuId, MenuItemType,
+};
+use gtk4::{gdk, gio, glib, prelude::*};
+use icon::to_cached_texture;
+use menu_model::{MenuModel, ModelRoot, ACTION_GROUP_NAME};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+/// The GTK4 menubars and context menu of a [`Menu`], all displayed from the same model.
+#[derive(Default)]
+pub(crate) struct Gtk4Menu {
+    // keyed by `window_id`
+    menubars: HashMap<u32, (gtk4::PopoverMenuBar, gtk4::ShortcutController)>,
+    // the `destroy` handlers connected to the windows in `menubars`
+    windows: HashMap<u32, (glib::WeakRef<gtk4::Window>, glib::SignalHandlerId)>,
+    popover: Option<gtk4::PopoverMenu>, // dedicated menu for context menus
+    model: Option<Rc<MenuModel>>,
+}
+
+impl Drop for Gtk4Menu {
+    fn drop(&mut self) {
+        for (window, handler) in self.windows.drain().map(|(_, w)| w) {
+            if let Some(window) = window.upgrade() {
+                window.disconnect(handler);
+            }
+        }
+
+        for (menu_bar, controller) in self.menubars.values() {
+            detach_menubar(menu_bar, controller);
+        }
+
+        if let Some(menu) = &self.popover {
+            if menu.parent().is_some() {
+                menu.unparent();
+            }
+        }
+    }
+}
+
+impl Gtk4Menu {
+    /// Returns the model shared by the menubars and the context menu of `menu`.
+    fn model(menu: &Rc<RefCell<Menu>>) -> Rc<MenuModel> {
+        let model = menu.borrow().gtk4.model.clone();
+        model.unwrap_or_else(|| {
+            let model = MenuModel::new(ModelRoot::Menu(Rc::downgrade(menu)));
+            register_observer(menu.borrow().observers(), model.clone());
+            menu.borrow_mut().gtk4.model = Some(model.clone());
+            model
+        })
+    }
+
+    pub(crate) fn init_for_window<W, C>(
+        menu: &Rc<RefCell<Menu>>,
+        window: &W,
+        container: Option<&C>,
+    ) -> crate::Result<()>
+    where
+        W: IsA<gtk4::Window>,
+        C: IsA<gtk4::Box>,
+    {
+        let menu_bar = Self::create_menubar_for_window(menu, window)?;
+
+        // add the menubar to the specified box, otherwise to the window
+        match container {
+            Some(container) => container.prepend(&menu_bar),
+            None => window.set_child(Some(&menu_bar)),
+        }
+
+        Ok(())
+    }
+
+    /// Creates the menubar for `window` and adds the shortcuts controller to it,
+    /// the menubar isn't added to any widget.
+    pub(crate) fn create_menubar_for_window<W>(
+        menu: &Rc<RefCell<Menu>>,
+        window: &W,
+    ) -> crate::Result<gtk4::PopoverMenuBar>
+    where
+        W: IsA<gtk4::Window>,
+    {
+        let id = window_id(window);
+        if menu.borrow().gtk4.menubars.contains_key(&id) {
+            return Err(crate::Error::AlreadyInitialized);
+        }
+
+        let model = Self::model(menu);
+        let menu_bar = gtk4::PopoverMenuBar::from_model(Some(&model.model));
+        menu_bar.insert_action_group(ACTION_GROUP_NAME, Some(&model.actions));
+
+        let window = window.upcast_ref::<gtk4::Window>();
+        let controller = gtk4::ShortcutController::for_model(&model.shortcuts);
+        window.add_controller(controller.clone());
+
+        let menu_ = Rc::downgrade(menu);
+        let handler = window.connect_destroy(move |_| {
+            if let Some(menu) = menu_.upgrade() {
+                Self::forget_window(&menu, id);
+            }
+        });
+
+        let gtk4 = &mut menu.borrow_mut().gtk4;
+        gtk4.windows.insert(id, (window.downgrade(), handler));
+        gtk4.menubars.insert(id, (menu_bar.clone(), controller));
+
+        Ok(menu_bar)
+    }
+
+    /// Drops the menubar of a destroyed window and removes the shortcuts controller from it.
+    fn forget_window(menu: &Rc<RefCell<Menu>>, id: u32) {
+        match menu.try_borrow_mut() {
+            Ok(mut menu) => {
+                if let Some((_, controller)) = menu.gtk4.remove_menubar(id) {
+                    if let Some(window) = controller.widget() {
+                        window.remove_controller(&controller);
+                    }
+                }
+            }
+            // the window was destroyed while the menu is in use, try again later
+            Err(_) => {
+                let menu = Rc::downgrade(menu);
+                glib::idle_add_local_once(move || {
+                    if let Some(menu) = menu.upgrade() {
+                        Self::forget_window(&menu, id);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Removes the menubar with `id` from our cache, without detaching it from the window.
+    fn remove_menubar(
+        &mut self,
+        id: u32,
+    ) -> Option<(gtk4::PopoverMenuBar, gtk4::ShortcutController)> {
+        if let Some((window, handler)) = self.windows.remove(&id) {
+            if let Some(window) = window.upgrade() {
+                window.disconnect(handler);
+            }
+        }
+
+        self.menubars.remove(&id)
+    }
+
+    pub(crate) fn remove_for_window<W>(&mut self, window: &W) -> crate::Result<()>
+    where
+        W: IsA<gtk4::Window>,
+    {
+        let (menu_bar, controller) = self
+            .remove_menubar(window_id(window))
+            .ok_or(crate::Error::NotInitialized)?;
+        detach_menubar(&menu_bar, &controller);
+        Ok(())
+    }
+
+    pub(crate) fn hide_for_window<W>(&self, window: &W) -> crate::Result<()>
+    where
+        W: IsA<gtk4::Window>,
+    {
+        self.menubars
+            .get(&window_id(window))
+            .ok_or(crate::Error::NotInitialized)?
+            .0
+            .set_visible(false);
+        Ok(())
+    }
+
+    pub(crate) fn show_for_window<W>(&self, window: &W) -> crate::Result<()>
+    where
+        W: IsA<gtk4::Window>,
+    {
+        self.menubars
+            .get(&window_id(window))
+            .ok_or(crate::Error::NotInitialized)?
+            .0
+            .set_visible(true);
+        Ok(())
+    }
+
+    pub(crate) fn is_visible_on_window<W>(&self, window: &W) -> bool
+    where
+        W: IsA<gtk4::Window>,
+    {
+        self.menubars
+            .get(&window_id(window))
+            .map(|m| m.0.is_visible())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn menubar_for_window<W>(&self, window: &W) -> Option<gtk4::PopoverMenuBar>
+    where
+        W: IsA<gtk4::Window>,
+    {
+        self.menubars.get(&window_id(window)).map(|m| m.0.clone())
+    }
+
+    pub(crate) fn to_gio_menu_model(
+        menu: &Rc<RefCell<Menu>>,
+    ) -> (gio::Menu, gio::SimpleActionGroup) {
+        let model = Self::model(menu);
+        (model.model.clone(), model.actions.clone())
+    }
+
+    pub(crate) fn context_menu(menu: &Rc<RefCell<Menu>>) -> gtk4::PopoverMenu {
+        let popover = menu.borrow().gtk4.popover.clone();
+        popover.unwrap_or_else(|| {
+            let popover = context_menu(&Self::model(menu));
+            menu.borrow_mut().gtk4.popover = Some(popover.clone());
+            popover
+        })
+    }
+}
+
+/// The GTK4 context menu of a submenu.
+#[derive(Debug, Default)]
+pub(crate) struct Gtk4Submenu {
+    popover: Option<gtk4::PopoverMenu>, // dedicated menu for context menus
+    model: Option<Rc<MenuModel>>,
+}
+
+impl Drop for Gtk4Submenu {
+    fn drop(&mut self) {
+        if let Some(menu) = &self.popover {
+            if menu.parent().is_some() {
+                menu.unparent();
+            }
+        }
+    }
+}
+
+impl Gtk4Submenu {
+    pub(crate) fn context_menu(submenu: &Rc<RefCell<MenuChild>>) -> gtk4::PopoverMenu {
+        let popover = submenu.borrow().gtk4.popover.clone();
+        popover.unwrap_or_else(|| {
+            let model = submenu.borrow().gtk4.model.clone();
+            let model = model.unwrap_or_else(|| {
+                let model = MenuModel::new(ModelRoot::Submenu(Rc::downgrade(submenu)));
+                register_observer(submenu.borrow().observers(), model.clone());
+                submenu.borrow_mut().gtk4.model = Some(model.clone());
+                model
+            });
+
+            let popover = context_menu(&model);
+            submenu.borrow_mut().gtk4.popover = Some(popover.clone());
+            popover
+        })
+    }
+}
+
+fn detach_menubar(menu_bar: &gtk4::PopoverMenuBar, controller: &gtk4::ShortcutController) {
+    if let Some(window) = controller.widget() {
+        window.remove_controller(controller);
+    }
+
+    if let Some(parent) = menu_bar.parent() {
+        if let Some(container) = parent.downcast_ref::<gtk4::Box>() {
+            container.remove(menu_bar);
+        } else if let Some(window) = parent.downcast_ref::<gtk4::Window>() {
+            window.set_child(None::<&gtk4::Widget>);
+        } else {
+            menu_bar.unparent();
+        }
+    }
+}
+
+fn context_menu(model: &MenuModel) -> gtk4::PopoverMenu {
+    let menu = gtk4::PopoverMenu::from_model(Some(&model.model));
+    menu.insert_action_group(ACTION_GROUP_NAME, Some(&model.actions));
+    menu.set_has_arrow(false);
+    menu.set_halign(gtk4::Align::Start);
+    menu
+}
+
+/// Pops up `menu` in `window` anchored as described by `options`.
+///
+/// Returns `false` if the menu couldn't be shown.
+fn popup_context_menu(
+    menu: &gtk4::PopoverMenu,
+    window: &gtk4::Window,
+    options: &ContextMenuOptions,
+) -> bool {
+    if menu.parent().as_ref() != Some(window.upcast_ref()) {
+        if menu.parent().is_some() {
+            menu.unparent();
+        }
+        menu.set_parent(window);
+    }
+
+    let scale_factor = window.scale_factor() as f64;
+    let rect = match &options.anchor {
+        ContextMenuAnchor::Cursor => {
+            let (x, y) = pointer_position(window).unwrap_or_default();
+            gdk::Rectangle::new(x, y, 1, 1)
+        }
+        ContextMenuAnchor::Position(position) => {
+            let (x, y) = position.to_logical::<i32>(scale_factor).into();
+            gdk::Rectangle::new(x, y, 1, 1)
+        }
+        ContextMenuAnchor::Rect { position, size } => {
+            let (x, y) = position.to_logical::<i32>(scale_factor).into();
+            let (width, height) = size.to_logical::<i32>(scale_factor).into();
+            gdk::Rectangle::new(x, y, width, height)
+        }
+        ContextMenuAnchor::Widget(_) => return false,
+        ContextMenuAnchor::Gtk4Widget(anchor) => {
+            let Some(bounds) = anchor.compute_bounds(window) else {
+                return false;
+            };
+            gdk::Rectangle::new(
+                bounds.x() as i32,
+                bounds.y() as i32,
+                bounds.width() as i32,
+                bounds.height() as i32,
+            )
+        }
+    };
+    menu.set_pointing_to(Some(&rect));
+    // popovers are always centered on a side of the anchor,
+    // pick the one the menu gravity points away from
+    menu.set_position(match options.menu_gravity {
+        ContextMenuGravity::SouthWest
+        | ContextMenuGravity::South
+        | ContextMenuGravity::SouthEast => gtk4::PositionType::Top,
+        ContextMenuGravity::West => gtk4::PositionType::Right,
+        ContextMenuGravity::East => gtk4::PositionType::Left,
+        ContextMenuGravity::NorthWest
+        | ContextMenuGravity::North
+        | ContextMenuGravity::NorthEast
+        | ContextMenuGravity::Center => gtk4::PositionType::Bottom,
+    });
+    menu.popup();
+
+    menu.is_visible()
+}
+
+/// Shows `menu` as a context menu and iterates the main context until it is closed.
+pub(crate) fn show_context_menu(
+    menu: &gtk4::PopoverMenu,
+    window: &gtk4::Window,
+    options: &ContextMenuOptions,
+) -> bool {
+    if !popup_context_menu(menu, window, options) {
+        return false;
+    }
+
+    let context = glib::MainContext::default();
+    while menu.is_visible() {
+        context.iteration(true);
+    }
+
+    true
+}
+
+/// Shows `menu` as a context menu and returns right away,
+/// `callback` is called from the main context once the menu is closed.
+pub(crate) fn show_context_menu_with_callback(
+    menu: &gtk4::PopoverMenu,
+    window: &gtk4::Window,
+    options: &ContextMenuOptions,
+    callback: Box<dyn FnOnce(bool)>,
+) {
+    if !popup_context_menu(menu, window, options) {
+        glib::idle_add_local_once(move || callback(false));
+        return;
+    }
+
+    let handler = Rc::new(Cell::new(None));
+    let callback = Cell::new(Some(callback));
+    let handler_ = handler.clone();
+    let id = menu.connect_closed(move |menu| {
+        if let Some(id) = handler_.take() {
+            menu.disconnect(id);
+        }
+        if let Some(callback) = callback.take() {
+            // `closed` is emitted before the activated item's action runs,
+            // so defer the callback until after it
+            glib::idle_add_local_once(move || callback(true));
+        }
+    });
+    handler.set(Some(id));
+}
+
+/// Returns the id `window` is known by in the maps of menus, see [`super::window_id::window_id`].
+fn window_id(window: &impl IsA<gtk4::Window>) -> u32 {
+    unsafe { super::window_id::window_id(window.as_ref().as_ptr().cast()) }
+}
+
+/// Returns the position of the pointer relative to `window`.
+fn pointer_position(window: &gtk4::Window) -> Option<(i32, i32)> {
+    let surface = window.surface()?;
+    let pointer = WidgetExt::display(window).default_seat()?.pointer()?;
+    let (x, y, _) = surface.device_position(&pointer)?;
+    let (dx, dy) = window.surface_transform();
+    Some(((x - dx) as i32, (y - dy) as i32))
+}
+
+#[cfg(feature = "libxdo")]
+fn xdo_keys(item_type: &PredefinedMenuItemType) -> &str {
+    match item_type {
+        PredefinedMenuItemType::Copy => "ctrl+c",
+        PredefinedMenuItemType::Cut => "ctrl+X",
+        PredefinedMenuItemType::Paste => "ctrl+v",
+        PredefinedMenuItemType::SelectAll => "ctrl+a",
+        PredefinedMenuItemType::Undo => "ctrl+z",
+        PredefinedMenuItemType::Redo => "ctrl+y",
+        _ => unreachable!(),
+    }
+}
+
+/// Activates `child`, called by the actions of the menu models it is part of.
+fn activate_child(child: &Rc<RefCell<MenuChild>>) {
+    let (item_type, id, predefined_item_type) = {
+        let child = child.borrow();
+        (
+            child.item_type(),
+            child.id().clone(),
+            child.predefined_item_type().cloned(),
+        )
+    };
+
+    match item_type {
+        MenuItemType::Check => {
+            let checked = !child.borrow().is_checked();
+            child.borrow_mut().set_checked(checked);
+            MenuEvent::send(crate::MenuEvent { id });
+        }
+        MenuItemType::Predefined => {
+            activate_predefined_item(&predefined_item_type.unwrap(), &id, active_window())
+        }
+        _ => MenuEvent::send(crate::MenuEvent { id }),
+    }
+}
+
+/// Performs the action of a predefined menu item, `window` is the window
+/// the item was activated from, if any.
+fn activate_predefined_item(
+    item_type: &PredefinedMenuItemType,
+    id: &MenuId,
+    window: Option<gtk4::Window>,
+) {
+    match item_type {
+        PredefinedMenuItemType::Copy
+        | PredefinedMenuItemType::Cut
+        | PredefinedMenuItemType::Paste
+        | PredefinedMenuItemType::SelectAll
+        | PredefinedMenuItemType::Undo
+        | PredefinedMenuItemType::Redo
+            if !activate_clipboard_action(window.as_ref(), item_type) =>
+        {
+            // only works on X11
+            #[cfg(feature = "libxdo")]
+            if let Ok(xdo) = libxdo::XDo::new(None) {
+                let _ = xdo.send_keysequence(xdo_keys(item_type), 0);
+            }
+        }
+        PredefinedMenuItemType::Minimize
+        | PredefinedMenuItemType::Maximize
+        | PredefinedMenuItemType::Fullscreen
+        | PredefinedMenuItemType::Hide
+        | PredefinedMenuItemType::CloseWindow => {
+            let Some(window) = window else {
+                return;
+            };
+            match item_type {
+                PredefinedMenuItemType::Minimize => window.minimize(),
+                PredefinedMenuItemType::Maximize => {
+                    if window.is_maximized() {
+                        window.unmaximize()
+                    } else {
+                        window.maximize()
+                    }
+                }
+                PredefinedMenuItemType::Fullscreen => {
+                    if window.is_fullscreen() {
+                        window.unfullscreen()
+                    } else {
+                        window.fullscreen()
+                    }
+                }
+                PredefinedMenuItemType::Hide => window.set_visible(false),
+                PredefinedMenuItemType::CloseWindow => window.close(),
+                _ => unreachable!(),
+            }
+        }
+        PredefinedMenuItemType::Quit => {
+            MenuEvent::send(crate::MenuEvent { id: id.clone() });
+            if let Some(app) = gio::Application::default() {
+                app.quit();
+            }
+        }
+        PredefinedMenuItemType::About(Some(metadata)) => {
+            let dialog = ABOUT_DIALOG_HANDLER
+                .with(|handler| handler.borrow().as_ref().map(|handler| handler(metadata)));
+            let dialog = dialog.unwrap_or_else(|| about_dialog(metadata).upcast());
+            dialog.set_transient_for(window.as_ref());
+            dialog.present();
+        }
+        _ => {}
+    }
+}
+
+/// Builds the default, non-modal, about dialog for `metadata`.
+fn about_dialog(metadata: &AboutMetadata) -> gtk4::AboutDialog {
+    let mut builder = gtk4::AboutDialog::builder().resizable(false);
+
+    if let Some(name) = &metadata.name {
+        builder = builder.program_name(name);
+    }
+    if let Some(version) = &metadata.full_version() {
+        builder = builder.version(version);
+    }
+    if let Some(authors) = &metadata.authors {
+        builder = builder.authors(authors.clone());
+    }
+    if let Some(artists) = &metadata.artists {
+        builder = builder.artists(artists.clone());
+    }
+    if let Some(documenters) = &metadata.documenters {
+        builder = builder.documenters(documenters.clone());
+    }
+    if let Some(translators) = &metadata.translators {
+        builder = builder.translator_credits(translators);
+    }
+    if let Some(comments) = &metadata.comments {
+        builder = builder.comments(comments);
+    }
+    if let Some(copyright) = &metadata.copyright {
+        builder = builder.copyright(copyright);
+    }
+    if let Some(license_kind) = metadata.license_kind {
+        builder = builder.license_type(license_kind.into());
+    } else if let Some(license) = &metadata.license {
+        builder = builder.license(license);
+    }
+    if let Some(website) = &metadata.website {
+        builder = builder.website(website);
+    }
+    if let Some(website_label) = &metadata.website_label {
+        builder = builder.website_label(website_label);
+    }
+    if let Some(icon) = &metadata.icon {
+        builder = builder.logo(&to_cached_texture(&icon.inner));
+    }
+
+    let about = builder.build();
+    if let Some(credits) = &metadata.credits {
+        let credits = credits
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .collect::<Vec<_>>();
+        if !credits.is_empty() {
+            about.add_credit_section("Credits", &credits);
+        }
+    }
+
+    about
+}
+
+impl From<LicenseKind> for gtk4::License {
+    fn from(kind: LicenseKind) -> Self {
+        match kind {
+            LicenseKind::Gpl20 => gtk4::License::Gpl20,
+            LicenseKind::Gpl30 => gtk4::License::Gpl30,
+            LicenseKind::Lgpl21 => gtk4::License::Lgpl21,
+            LicenseKind::Lgpl30 => gtk4::License::Lgpl30,
+            LicenseKind::Bsd => gtk4::License::Bsd,
+            LicenseKind::MitX11 => gtk4::License::MitX11,
+            LicenseKind::Artistic => gtk4::License::Artistic,
+            LicenseKind::Gpl20Only => gtk4::License::Gpl20Only,
+            LicenseKind::Gpl30Only => gtk4::License::Gpl30Only,
+            LicenseKind::Lgpl21Only => gtk4::License::Lgpl21Only,
+            LicenseKind::Lgpl30Only => gtk4::License::Lgpl30Only,
+            LicenseKind::Agpl30 => gtk4::License::Agpl30,
+            LicenseKind::Agpl30Only => gtk4::License::Agpl30Only,
+            LicenseKind::Bsd3 => gtk4::License::Bsd3,
+            LicenseKind::Apache20 => gtk4::License::Apache20,
+            LicenseKind::Mpl20 => gtk4::License::Mpl20,
+        }
+    }
+}
+
+type AboutDialogHandler = Box<dyn Fn(&AboutMetadata) -> gtk4::Window>;
+
+thread_local! {
+    /// Set by [`AboutMetadata::set_gtk4_dialog_handler`] to replace [`about_dialog`].
+    static ABOUT_DIALOG_HANDLER: RefCell<Option<AboutDialogHandler>> = RefCell::new(None);
+}
+
+pub(crate) fn set_about_dialog_handler(handler: Option<AboutDialogHandler>) {
+    ABOUT_DIALOG_HANDLER.with(|h| *h.borrow_mut() = handler);
+}
+
+/// Performs the action of a `Copy`, `Cut`, `Paste`, `SelectAll`, `Undo` or `Redo` item
+/// on the focused widget of `window`, through the actions GTK4 text widgets install.
+///
+/// Returns `false` if there is no focused widget or it doesn't support the action.
+fn activate_clipboard_action(
+    window: Option<&gtk4::Window>,
+    item_type: &PredefinedMenuItemType,
+) -> bool {
+    let Some(widget) = window.and_then(GtkWindowExt::focus) else {
+        return false;
+    };
+
+    let action = match item_type {
+        PredefinedMenuItemType::Copy => "clipboard.copy",
+        PredefinedMenuItemType::Cut => "clipboard.cut",
+        PredefinedMenuItemType::Paste => "clipboard.paste",
+        PredefinedMenuItemType::SelectAll => "selection.select-all",
+        PredefinedMenuItemType::Undo => "text.undo",
+        PredefinedMenuItemType::Redo => "text.redo",
+        _ => return false,
+    };
+
+    widget.activate_action(action, None).is_ok()
+}
+
+fn active_window() -> Option<gtk4::Window> {
+    gtk4::Window::list_toplevels()
+        .into_iter()
+        .filter_map(|w| w.downcast::<gtk4::Window>().ok())
+        .find(|w| w.is_active())
+}
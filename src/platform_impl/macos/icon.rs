@@ -17,6 +17,14 @@ impl PlatformIcon {
         Ok(PlatformIcon(RgbaIcon::from_rgba(rgba, width, height)?))
     }
 
+    pub(crate) fn rgba_icon(&self) -> &RgbaIcon {
+        &self.0
+    }
+
+    pub(crate) fn into_rgba_icon(self) -> RgbaIcon {
+        self.0
+    }
+
     pub fn get_size(&self) -> (u32, u32) {
         (self.0.width, self.0.height)
     }
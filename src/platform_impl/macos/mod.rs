@@ -9,6 +9,7 @@ mod util;
 pub(crate) use icon::PlatformIcon;
 
 use std::{
+    any::Any,
     cell::{Cell, RefCell},
     collections::HashMap,
     ffi::c_void,
@@ -19,14 +20,14 @@ use std::{
 use objc2::{
     declare_class, msg_send_id, mutability,
     rc::Retained,
-    runtime::{AnyObject, Sel},
+    runtime::{AnyObject, NSObject, NSObjectProtocol, ProtocolObject, Sel},
     sel, ClassType, DeclaredClass,
 };
 use objc2_app_kit::{
     NSAboutPanelOptionApplicationIcon, NSAboutPanelOptionApplicationName,
     NSAboutPanelOptionApplicationVersion, NSAboutPanelOptionCredits, NSAboutPanelOptionVersion,
     NSApplication, NSControlStateValueOff, NSControlStateValueOn, NSEvent, NSEventModifierFlags,
-    NSImage, NSImageName, NSMenu, NSMenuItem, NSRunningApplication, NSView,
+    NSImage, NSImageName, NSMenu, NSMenuDelegate, NSMenuItem, NSRunningApplication, NSView,
 };
 use objc2_foundation::{
     ns_string, MainThreadMarker, NSAttributedString, NSDictionary, NSInteger, NSPoint, NSSize,
@@ -40,7 +41,7 @@ use crate::{
     icon::{Icon, NativeIcon},
     items::*,
     util::{AddOp, Counter},
-    IsMenuItem, MenuEvent, MenuId, MenuItemKind, MenuItemType,
+    IsMenuItem, MenuEvent, MenuHighlightEvent, MenuId, MenuItemKind, MenuItemType,
 };
 
 static COUNTER: Counter = Counter::new();
@@ -83,8 +84,9 @@ impl Menu {
             MainThreadMarker::new().expect("`muda::Menu` can only be created on the main thread");
         let ns_menu = NSMenu::new(mtm);
         unsafe { ns_menu.setAutoenablesItems(false) };
+        set_menu_delegate(&ns_menu);
         Self {
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             ns_menu: NsMenuRef(COUNTER.next(), ns_menu),
             children: Vec::new(),
         }
@@ -190,23 +192,27 @@ impl Menu {
 pub struct MenuChild {
     // shared fields between submenus and menu items
     item_type: MenuItemType,
-    id: MenuId,
-    text: String,
-    enabled: bool,
+    pub(crate) id: MenuId,
+    pub(crate) text: String,
+    pub(crate) enabled: bool,
+    pub(crate) tooltip: Option<String>,
+    data: Option<Rc<dyn Any>>,
+    // the menus and submenus this item was added to, once per time it was added
+    pub(crate) parents: Vec<crate::menu_parent::WeakMenuParent>,
 
     ns_menu_items: HashMap<u32, Vec<Retained<NSMenuItem>>>,
 
     // menu item fields
-    accelerator: Option<Accelerator>,
+    pub(crate) accelerator: Option<Accelerator>,
 
     // predefined menu item fields
-    predefined_item_type: Option<PredefinedMenuItemType>,
+    pub(crate) predefined_item_type: Option<PredefinedMenuItemType>,
 
     // check menu item fields
     checked: Cell<bool>,
 
     // icon menu item fields
-    icon: Option<Icon>,
+    pub(crate) icon: Option<Icon>,
     native_icon: Option<NativeIcon>,
 
     // submenu fields
@@ -258,7 +264,7 @@ impl MenuChild {
             item_type: MenuItemType::MenuItem,
             text: strip_mnemonic(text),
             enabled,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             accelerator,
             checked: Cell::new(false),
             children: None,
@@ -267,6 +273,9 @@ impl MenuChild {
             ns_menu: None,
             ns_menu_items: HashMap::new(),
             ns_menus: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -281,12 +290,13 @@ impl MenuChild {
         Self {
             item_type: MenuItemType::Submenu,
             text: strip_mnemonic(text),
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             enabled,
             children: Some(Vec::new()),
             ns_menu: Some(NsMenuRef(COUNTER.next(), unsafe {
                 let menu = NSMenu::new(mtm);
                 menu.setAutoenablesItems(false);
+                set_menu_delegate(&menu);
                 menu
             })),
             accelerator: None,
@@ -295,6 +305,9 @@ impl MenuChild {
             native_icon: None,
             ns_menu_items: HashMap::new(),
             ns_menus: Some(HashMap::new()),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -322,8 +335,11 @@ impl MenuChild {
             item_type: MenuItemType::Predefined,
             text,
             enabled: true,
-            id: MenuId(COUNTER.next().to_string()),
+            id: MenuId::auto(COUNTER.next()),
             accelerator,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: Some(item_type),
             checked: Cell::new(false),
             children: None,
@@ -346,7 +362,7 @@ impl MenuChild {
             item_type: MenuItemType::Check,
             text: text.to_string(),
             enabled,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             accelerator,
             checked: Cell::new(checked),
             children: None,
@@ -355,6 +371,9 @@ impl MenuChild {
             ns_menu: None,
             ns_menu_items: HashMap::new(),
             ns_menus: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -370,7 +389,7 @@ impl MenuChild {
             item_type: MenuItemType::Icon,
             text: text.to_string(),
             enabled,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             icon,
             accelerator,
             checked: Cell::new(false),
@@ -379,6 +398,9 @@ impl MenuChild {
             ns_menu: None,
             ns_menu_items: HashMap::new(),
             ns_menus: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
@@ -394,7 +416,7 @@ impl MenuChild {
             item_type: MenuItemType::Icon,
             text: text.to_string(),
             enabled,
-            id: id.unwrap_or_else(|| MenuId(COUNTER.next().to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(COUNTER.next())),
             native_icon,
             accelerator,
             checked: Cell::new(false),
@@ -403,9 +425,34 @@ impl MenuChild {
             ns_menu: None,
             ns_menu_items: HashMap::new(),
             ns_menus: None,
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
         }
     }
+
+    /// Creates the icon menu item of [`MenuChild::duplicate`], with the native icon
+    /// of this item if it has one, animated icons aren't supported.
+    pub(crate) fn duplicate_icon(&self, id: Option<MenuId>) -> Self {
+        if self.native_icon.is_some() {
+            return Self::new_native_icon(
+                &self.text,
+                self.enabled,
+                self.native_icon,
+                self.accelerator,
+                id,
+            );
+        }
+
+        Self::new_icon(
+            &self.text,
+            self.enabled,
+            self.icon.clone(),
+            self.accelerator,
+            id,
+        )
+    }
 }
 
 /// Shared methods
@@ -437,6 +484,28 @@ impl MenuChild {
         }
     }
 
+    pub fn tooltip(&self) -> Option<String> {
+        self.tooltip.clone()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        self.tooltip = tooltip.map(ToString::to_string);
+        let tooltip = tooltip.map(NSString::from_str);
+        for ns_items in self.ns_menu_items.values() {
+            for ns_item in ns_items {
+                unsafe { ns_item.setToolTip(tooltip.as_deref()) };
+            }
+        }
+    }
+
+    pub fn data(&self) -> Option<Rc<dyn Any>> {
+        self.data.clone()
+    }
+
+    pub fn set_data(&mut self, data: Option<Rc<dyn Any>>) {
+        self.data = data;
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
@@ -706,6 +775,7 @@ impl MenuChild {
 
             ns_menu_item.setSubmenu(Some(&ns_submenu));
             ns_submenu.setAutoenablesItems(false);
+            set_menu_delegate(&ns_submenu);
 
             ns_menu_item.setEnabled(self.enabled);
         }
@@ -873,13 +943,19 @@ impl MenuChild {
     }
 
     fn make_ns_item_for_menu(&mut self, menu_id: u32) -> crate::Result<Retained<NSMenuItem>> {
-        match self.item_type {
+        let ns_menu_item = match self.item_type {
             MenuItemType::Submenu => self.create_ns_item_for_submenu(menu_id),
             MenuItemType::MenuItem => self.create_ns_item_for_menu_item(menu_id),
             MenuItemType::Predefined => self.create_ns_item_for_predefined_menu_item(menu_id),
             MenuItemType::Check => self.create_ns_item_for_check_menu_item(menu_id),
             MenuItemType::Icon => self.create_ns_item_for_icon_menu_item(menu_id),
+        }?;
+
+        if let Some(tooltip) = &self.tooltip {
+            unsafe { ns_menu_item.setToolTip(Some(&NSString::from_str(tooltip))) };
         }
+
+        Ok(ns_menu_item)
     }
 }
 
@@ -912,22 +988,7 @@ impl PredefinedMenuItemType {
 
 impl dyn IsMenuItem + '_ {
     fn make_ns_item_for_menu(&self, menu_id: u32) -> crate::Result<Retained<NSMenuItem>> {
-        match self.kind() {
-            MenuItemKind::Submenu(i) => i.inner.borrow_mut().create_ns_item_for_submenu(menu_id),
-            MenuItemKind::MenuItem(i) => i.inner.borrow_mut().create_ns_item_for_menu_item(menu_id),
-            MenuItemKind::Predefined(i) => i
-                .inner
-                .borrow_mut()
-                .create_ns_item_for_predefined_menu_item(menu_id),
-            MenuItemKind::Check(i) => i
-                .inner
-                .borrow_mut()
-                .create_ns_item_for_check_menu_item(menu_id),
-            MenuItemKind::Icon(i) => i
-                .inner
-                .borrow_mut()
-                .create_ns_item_for_icon_menu_item(menu_id),
-        }
+        self.child().borrow_mut().make_ns_item_for_menu(menu_id)
     }
 }
 
@@ -1071,6 +1132,59 @@ impl MenuItem {
     }
 }
 
+declare_class!(
+    struct MenuDelegate;
+
+    unsafe impl ClassType for MenuDelegate {
+        type Super = NSObject;
+        type Mutability = mutability::MainThreadOnly;
+        const NAME: &'static str = "MudaMenuDelegate";
+    }
+
+    impl DeclaredClass for MenuDelegate {}
+
+    unsafe impl NSObjectProtocol for MenuDelegate {}
+
+    unsafe impl NSMenuDelegate for MenuDelegate {
+        #[method(menu:willHighlightItem:)]
+        fn menu_will_highlight_item(&self, _menu: &NSMenu, item: Option<&NSMenuItem>) {
+            // only our own items know their `MenuChild`, submenu items don't
+            let Some(item) = item.filter(|item| item.is_kind_of::<MenuItem>()) else {
+                return;
+            };
+            let item = unsafe { &*(item as *const NSMenuItem as *const MenuItem) };
+            if let Some(child) = unsafe { item.ivars().get().as_ref() } {
+                MenuHighlightEvent::send(crate::MenuHighlightEvent {
+                    id: child.id.clone(),
+                    tooltip: child.tooltip.clone(),
+                });
+            }
+        }
+    }
+);
+
+impl MenuDelegate {
+    fn new(mtm: MainThreadMarker) -> Retained<Self> {
+        let this = mtm.alloc().set_ivars(());
+        unsafe { msg_send_id![super(this), init] }
+    }
+}
+
+thread_local! {
+    /// The delegate of every menu, which sends their highlight events.
+    ///
+    /// Menus only keep a weak reference to their delegate, so it is kept here.
+    static MENU_DELEGATE: Retained<MenuDelegate> = MenuDelegate::new(
+        MainThreadMarker::new().expect("can only create menus on the main thread"),
+    );
+}
+
+fn set_menu_delegate(menu: &NSMenu) {
+    MENU_DELEGATE.with(|delegate| unsafe {
+        menu.setDelegate(Some(ProtocolObject::from_ref(&**delegate)));
+    });
+}
+
 fn menuitem_set_icon(menuitem: &NSMenuItem, icon: Option<&Icon>) {
     if let Some(icon) = icon {
         unsafe {
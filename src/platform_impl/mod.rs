@@ -8,16 +8,23 @@ mod platform;
 #[cfg(target_os = "linux")]
 #[path = "gtk/mod.rs"]
 mod platform;
+// the GTK4 views are built on the GTK3 menus and items
+#[cfg(all(target_os = "linux", feature = "gtk4"))]
+pub(crate) mod gtk4;
+#[cfg(target_os = "linux")]
+mod observer;
 #[cfg(target_os = "macos")]
 #[path = "macos/mod.rs"]
 mod platform;
+#[cfg(target_os = "linux")]
+mod window_id;
 
 use std::{
     cell::{Ref, RefCell, RefMut},
     rc::Rc,
 };
 
-use crate::{items::*, IsMenuItem, MenuItemKind, MenuItemType};
+use crate::{items::*, IsMenuItem, MenuId, MenuItemKind, MenuItemType};
 
 pub(crate) use self::platform::*;
 
@@ -88,6 +95,11 @@ impl MenuItemKind {
         }
     }
 
+    /// Whether `other` is this same item.
+    pub(crate) fn ptr_eq(&self, other: &MenuItemKind) -> bool {
+        Rc::ptr_eq(&self.as_ref().child(), &other.as_ref().child())
+    }
+
     pub(crate) fn child(&self) -> Ref<MenuChild> {
         match self {
             MenuItemKind::MenuItem(i) => i.inner.borrow(),
@@ -98,6 +110,41 @@ impl MenuItemKind {
         }
     }
 
+    /// Creates a copy of this item, and of its items if it's a submenu, that
+    /// shares no state with it, using the ids returned by `id_mapper`.
+    pub(crate) fn deep_clone(
+        &self,
+        id_mapper: &mut dyn FnMut(&MenuId) -> Option<MenuId>,
+    ) -> crate::Result<MenuItemKind> {
+        self.deep_clone_inner(id_mapper, &mut Vec::new())
+    }
+
+    fn deep_clone_inner(
+        &self,
+        id_mapper: &mut dyn FnMut(&MenuId) -> Option<MenuId>,
+        path: &mut Vec<MenuItemKind>,
+    ) -> crate::Result<MenuItemKind> {
+        let child = self.child().duplicate(id_mapper(self.id()));
+        let child = Rc::new(RefCell::new(child));
+        let kind = child.borrow().kind(child.clone());
+
+        // a submenu that is its own descendant is copied without its items
+        if let (MenuItemKind::Submenu(from), MenuItemKind::Submenu(to)) = (self, &kind) {
+            if !path.iter().any(|s| s.ptr_eq(self)) {
+                path.push(self.clone());
+                let items = from
+                    .items()
+                    .iter()
+                    .map(|i| i.deep_clone_inner(id_mapper, path))
+                    .collect::<crate::Result<Vec<_>>>()?;
+                path.pop();
+                to.append_items(&items.iter().map(|i| i.as_ref()).collect::<Vec<_>>())?;
+            }
+        }
+
+        Ok(kind)
+    }
+
     pub(crate) fn child_mut(&self) -> RefMut<MenuChild> {
         match self {
             MenuItemKind::MenuItem(i) => i.inner.borrow_mut(),
@@ -108,3 +155,34 @@ impl MenuItemKind {
         }
     }
 }
+
+impl MenuChild {
+    /// Creates a new item of the same type and with the same state as this one,
+    /// without its items, data or parents, with `id` or a new id if `None`.
+    ///
+    /// Icon menu items are created by each platform, see `MenuChild::duplicate_icon`.
+    pub(crate) fn duplicate(&self, id: Option<MenuId>) -> Self {
+        let mut child = match self.item_type() {
+            MenuItemType::MenuItem => Self::new(&self.text, self.enabled, self.accelerator, id),
+            MenuItemType::Submenu => Self::new_submenu(&self.text, self.enabled, id),
+            MenuItemType::Predefined => {
+                let item_type = self.predefined_item_type.clone().unwrap();
+                let mut child = Self::new_predefined(item_type, Some(self.text.clone()));
+                if let Some(id) = id {
+                    child.id = id;
+                }
+                child
+            }
+            MenuItemType::Check => Self::new_check(
+                &self.text,
+                self.enabled,
+                self.is_checked(),
+                self.accelerator,
+                id,
+            ),
+            MenuItemType::Icon => self.duplicate_icon(id),
+        };
+        child.tooltip = self.tooltip.clone();
+        child
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::MenuId;
+
+/// Notified of the changes made to a menu and its items, used by the
+/// views that mirror a menu instead of owning widgets in it, like menu models.
+pub(crate) trait MenuObserver {
+    /// The items of a menu, or the text, icon or accelerator of an item changed.
+    fn layout_changed(&self);
+
+    fn enabled_changed(&self, _id: &MenuId, _enabled: bool) {
+        self.layout_changed()
+    }
+
+    fn checked_changed(&self, _id: &MenuId, _checked: bool) {
+        self.layout_changed()
+    }
+}
+
+pub(crate) type MenuObservers = Rc<RefCell<Vec<Weak<dyn MenuObserver>>>>;
+
+pub(crate) fn notify_observers(observers: &MenuObservers, f: impl Fn(&dyn MenuObserver)) {
+    // collected first as observers may register themselves again
+    let observers = observers
+        .borrow()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect::<Vec<_>>();
+    for observer in observers {
+        f(&*observer);
+    }
+}
+
+pub(crate) fn register_observer(observers: &MenuObservers, observer: Rc<dyn MenuObserver>) {
+    let observer = Rc::downgrade(&observer);
+    let mut observers = observers.borrow_mut();
+    observers.retain(|o| o.strong_count() > 0);
+    if !observers.iter().any(|o| o.ptr_eq(&observer)) {
+        observers.push(observer);
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::ffi::c_void;
+
+use gtk::glib::gobject_ffi;
+
+use crate::util::Counter;
+
+static COUNTER: Counter = Counter::new();
+
+const KEY: &[u8] = b"muda-window-id\0";
+
+/// Returns the id a GTK3 or GTK4 window is known by in the maps of menus and items,
+/// assigned on first use and kept as data on the window object.
+///
+/// Unlike the window address, an id is never shared by two windows,
+/// even when a window is allocated where a destroyed one used to be.
+///
+/// # Safety
+///
+/// `window` must point to a live `GObject`.
+pub(crate) unsafe fn window_id(window: *mut c_void) -> u32 {
+    let window = window.cast::<gobject_ffi::GObject>();
+    // ids start at 1, so no data means no id was assigned yet
+    let id = gobject_ffi::g_object_get_data(window, KEY.as_ptr().cast()) as usize as u32;
+    if id != 0 {
+        return id;
+    }
+
+    let id = COUNTER.next();
+    gobject_ffi::g_object_set_data(window, KEY.as_ptr().cast(), id as usize as *mut c_void);
+    id
+}
@@ -4,19 +4,22 @@
 
 // taken from https://github.com/rust-windowing/winit/blob/92fdf5ba85f920262a61cee4590f4a11ad5738d1/src/platform_impl/windows/icon.rs
 
-use std::{fmt, io, mem, path::Path, sync::Arc};
+use std::{ffi::c_void, fmt, io, mem, path::Path, sync::Arc};
+
+use once_cell::sync::OnceCell;
 
 use windows_sys::{
     core::PCWSTR,
     Win32::{
         Foundation::RECT,
         Graphics::Gdi::{
-            CreateCompatibleDC, CreateDIBSection, DeleteDC, GetDC, ReleaseDC, SelectObject,
-            BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP,
+            CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDC, GetDIBits,
+            GetObjectW, ReleaseDC, SelectObject, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+            DIB_RGB_COLORS, HBITMAP,
         },
         UI::WindowsAndMessaging::{
-            CreateIcon, DestroyIcon, DrawIconEx, LoadImageW, DI_NORMAL, HICON, IMAGE_ICON,
-            LR_DEFAULTSIZE, LR_LOADFROMFILE,
+            CreateIcon, DestroyIcon, DrawIconEx, GetIconInfo, LoadImageW, DI_NORMAL, HICON,
+            ICONINFO, IMAGE_ICON, LR_DEFAULTSIZE, LR_LOADFROMFILE,
         },
     },
 };
@@ -32,8 +35,8 @@ impl Pixel {
 }
 
 impl RgbaIcon {
-    fn into_windows_icon(self) -> Result<WinIcon, BadIcon> {
-        let rgba = self.rgba;
+    fn to_windows_icon(&self) -> Result<WinIcon, BadIcon> {
+        let rgba = self.rgba.clone();
         let pixel_count = rgba.len() / PIXEL_SIZE;
         let mut and_mask = Vec::with_capacity(pixel_count);
         let pixels =
@@ -55,11 +58,126 @@ impl RgbaIcon {
             )
         };
         if !handle.is_null() {
-            Ok(WinIcon::from_handle(handle))
+            Ok(WinIcon::from_handle(
+                handle,
+                OnceCell::with_value(self.clone()),
+            ))
         } else {
             Err(BadIcon::OsError(io::Error::last_os_error()))
         }
     }
+
+    /// Reads back the pixels of an icon loaded by the OS,
+    /// or returns a transparent pixel if they can't be read.
+    unsafe fn from_handle(handle: HICON) -> Self {
+        let mut icon_info: ICONINFO = mem::zeroed();
+        if GetIconInfo(handle, &mut icon_info) == 0 {
+            return Self::transparent_pixel();
+        }
+
+        let result = if icon_info.hbmColor.is_null() {
+            Self::from_monochrome_mask(icon_info.hbmMask)
+        } else {
+            Self::from_hbitmap(icon_info.hbmColor)
+        };
+
+        DeleteObject(icon_info.hbmColor);
+        DeleteObject(icon_info.hbmMask);
+
+        result.unwrap_or_else(|_| Self::transparent_pixel())
+    }
+
+    fn transparent_pixel() -> Self {
+        RgbaIcon {
+            rgba: vec![0; PIXEL_SIZE],
+            width: 1,
+            height: 1,
+        }
+    }
+
+    unsafe fn from_hbitmap(hbitmap: HBITMAP) -> Result<Self, BadIcon> {
+        let (mut rgba, width, height) = read_bitmap(hbitmap)?;
+
+        // icons without an alpha channel are fully opaque
+        if rgba.chunks_exact(PIXEL_SIZE).all(|p| p[3] == 0) {
+            for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+                pixel[3] = u8::MAX;
+            }
+        }
+
+        RgbaIcon::from_rgba(rgba, width, height)
+    }
+
+    /// Monochrome icons have no color bitmap, only a mask with the AND mask above the XOR mask.
+    unsafe fn from_monochrome_mask(hbitmap: HBITMAP) -> Result<Self, BadIcon> {
+        let (mask, width, height) = read_bitmap(hbitmap)?;
+        let (and_mask, xor_mask) = mask.split_at(mask.len() / 2);
+        let rgba = and_mask
+            .chunks_exact(PIXEL_SIZE)
+            .zip(xor_mask.chunks_exact(PIXEL_SIZE))
+            .flat_map(|(and, xor)| {
+                // pixels set in the AND mask are transparent, or invert the screen
+                // if they are also set in the XOR mask, which can't be represented
+                if and[0] == 0 {
+                    [xor[0], xor[1], xor[2], u8::MAX]
+                } else {
+                    [0; PIXEL_SIZE]
+                }
+            })
+            .collect();
+
+        RgbaIcon::from_rgba(rgba, width, height / 2)
+    }
+}
+
+/// Reads the pixels of `hbitmap` as 32bpp RGBA data, returned with the bitmap dimensions.
+unsafe fn read_bitmap(hbitmap: HBITMAP) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+    let mut bitmap: BITMAP = mem::zeroed();
+    if GetObjectW(
+        hbitmap,
+        mem::size_of::<BITMAP>() as _,
+        &mut bitmap as *mut BITMAP as *mut c_void,
+    ) == 0
+    {
+        return Err(BadIcon::OsError(io::Error::last_os_error()));
+    }
+
+    let width = bitmap.bmWidth;
+    let height = bitmap.bmHeight;
+
+    let mut bitmap_info: BITMAPINFO = mem::zeroed();
+    bitmap_info.bmiHeader.biSize = mem::size_of::<BITMAPINFOHEADER>() as _;
+    bitmap_info.bmiHeader.biWidth = width;
+    // negative height for a top-down bitmap
+    bitmap_info.bmiHeader.biHeight = -height;
+    bitmap_info.bmiHeader.biPlanes = 1;
+    bitmap_info.bmiHeader.biBitCount = 32;
+    bitmap_info.bmiHeader.biCompression = BI_RGB as _;
+
+    let mut rgba = vec![0u8; width as usize * height as usize * PIXEL_SIZE];
+
+    let hdc = GetDC(std::ptr::null_mut());
+    let lines = GetDIBits(
+        hdc,
+        hbitmap,
+        0,
+        height as _,
+        rgba.as_mut_ptr() as *mut c_void,
+        &mut bitmap_info,
+        DIB_RGB_COLORS,
+    );
+    ReleaseDC(std::ptr::null_mut(), hdc);
+
+    if lines == 0 {
+        return Err(BadIcon::OsError(io::Error::last_os_error()));
+    }
+
+    for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+        // convert from bgra
+        pixel.swap(0, 2);
+    }
+
+    Ok((rgba, width as u32, height as u32))
 }
 
 #[derive(Debug)]
@@ -70,6 +188,8 @@ struct RaiiIcon {
 #[derive(Clone)]
 pub(crate) struct WinIcon {
     inner: Arc<RaiiIcon>,
+    // the pixels of icons loaded by the OS are only read back once they are needed
+    rgba: Arc<OnceCell<RgbaIcon>>,
 }
 
 unsafe impl Send for WinIcon {}
@@ -128,13 +248,23 @@ impl WinIcon {
 
     pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
         let rgba_icon = RgbaIcon::from_rgba(rgba, width, height)?;
-        rgba_icon.into_windows_icon()
+        rgba_icon.to_windows_icon()
+    }
+
+    pub(crate) fn rgba_icon(&self) -> &RgbaIcon {
+        self.rgba
+            .get_or_init(|| unsafe { RgbaIcon::from_handle(self.inner.handle) })
+    }
+
+    pub(crate) fn into_rgba_icon(self) -> RgbaIcon {
+        self.rgba_icon().clone()
     }
 
-    fn from_handle(handle: HICON) -> Self {
+    fn from_handle(handle: HICON, rgba: OnceCell<RgbaIcon>) -> Self {
         Self {
             #[allow(clippy::arc_with_non_send_sync)]
             inner: Arc::new(RaiiIcon { handle }),
+            rgba: Arc::new(rgba),
         }
     }
 
@@ -158,7 +288,7 @@ impl WinIcon {
             )
         };
         if !handle.is_null() {
-            Ok(WinIcon::from_handle(handle as HICON))
+            Ok(WinIcon::from_handle(handle as HICON, OnceCell::new()))
         } else {
             Err(BadIcon::OsError(io::Error::last_os_error()))
         }
@@ -181,7 +311,7 @@ impl WinIcon {
             )
         };
         if !handle.is_null() {
-            Ok(WinIcon::from_handle(handle as HICON))
+            Ok(WinIcon::from_handle(handle as HICON, OnceCell::new()))
         } else {
             Err(BadIcon::OsError(io::Error::last_os_error()))
         }
@@ -16,9 +16,11 @@ use crate::{
     icon::{Icon, NativeIcon},
     items::PredefinedMenuItemType,
     util::{AddOp, Counter},
-    AboutMetadata, IsMenuItem, MenuEvent, MenuId, MenuItemKind, MenuItemType, MenuTheme,
+    AboutMetadata, IsMenuItem, MenuEvent, MenuHighlightEvent, MenuId, MenuItemKind, MenuItemType,
+    MenuTheme,
 };
 use std::{
+    any::Any,
     cell::{RefCell, RefMut},
     collections::HashMap,
     fmt::Debug,
@@ -36,13 +38,13 @@ use windows_sys::Win32::{
         WindowsAndMessaging::{
             AppendMenuW, CreateAcceleratorTableW, CreateMenu, CreatePopupMenu,
             DestroyAcceleratorTable, DestroyMenu, DrawMenuBar, EnableMenuItem, GetCursorPos,
-            GetMenu, GetMenuItemInfoW, InsertMenuW, PostMessageW, PostQuitMessage, RemoveMenu,
-            SendMessageW, SetForegroundWindow, SetMenu, SetMenuItemInfoW, ShowWindow,
+            GetMenu, GetMenuItemInfoW, GetSubMenu, InsertMenuW, PostMessageW, PostQuitMessage,
+            RemoveMenu, SendMessageW, SetForegroundWindow, SetMenu, SetMenuItemInfoW, ShowWindow,
             TrackPopupMenu, HACCEL, HMENU, MENUITEMINFOW, MFS_CHECKED, MFS_DISABLED, MF_BYCOMMAND,
             MF_BYPOSITION, MF_CHECKED, MF_DISABLED, MF_ENABLED, MF_GRAYED, MF_POPUP, MF_SEPARATOR,
             MF_STRING, MF_UNCHECKED, MIIM_BITMAP, MIIM_STATE, MIIM_STRING, SW_HIDE, SW_MAXIMIZE,
-            SW_MINIMIZE, TPM_LEFTALIGN, TPM_RETURNCMD, WM_CLOSE, WM_COMMAND, WM_NCACTIVATE,
-            WM_NCPAINT,
+            SW_MINIMIZE, TPM_LEFTALIGN, TPM_RETURNCMD, WM_CLOSE, WM_COMMAND, WM_MENUSELECT,
+            WM_NCACTIVATE, WM_NCPAINT,
         },
     },
 };
@@ -157,7 +159,7 @@ impl Menu {
     pub fn new(id: Option<MenuId>) -> Self {
         let internal_id = COUNTER.next();
         Self {
-            id: id.unwrap_or_else(|| MenuId::new(internal_id.to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(internal_id)),
             internal_id,
             hmenu: unsafe { CreateMenu() },
             hpopupmenu: unsafe { CreatePopupMenu() },
@@ -425,7 +427,9 @@ impl Menu {
         hwnd: isize,
         position: Option<Position>,
     ) -> bool {
-        let rc = show_context_menu(hwnd as _, self.hpopupmenu, position);
+        let rc = show_context_menu(hwnd as _, self.hpopupmenu, position, &|id| {
+            self.find_by_id(id)
+        });
         if let Some(item) = rc.and_then(|rc| self.find_by_id(rc)) {
             unsafe {
                 menu_selected(hwnd as _, &mut item.borrow_mut());
@@ -454,24 +458,28 @@ type ParentMenu = (HMENU, Option<Rc<RefCell<HashMap<Hwnd, MenuTheme>>>>);
 pub(crate) struct MenuChild {
     // shared fields between submenus and menu items
     item_type: MenuItemType,
-    text: String,
-    enabled: bool,
+    pub(crate) text: String,
+    pub(crate) enabled: bool,
+    pub(crate) tooltip: Option<String>,
+    data: Option<Rc<dyn Any>>,
+    // the menus and submenus this item was added to, once per time it was added
+    pub(crate) parents: Vec<crate::menu_parent::WeakMenuParent>,
     parents_hemnu: Vec<ParentMenu>,
     root_menu_haccel_stores: HashMap<u32, Rc<RefCell<AccelWrapper>>>,
 
     // menu item fields
     internal_id: u32,
-    id: MenuId,
-    accelerator: Option<Accelerator>,
+    pub(crate) id: MenuId,
+    pub(crate) accelerator: Option<Accelerator>,
 
     // predefined menu item fields
-    predefined_item_type: Option<PredefinedMenuItemType>,
+    pub(crate) predefined_item_type: Option<PredefinedMenuItemType>,
 
     // check menu item fields
     checked: bool,
 
     // icon menu item fields
-    icon: Option<Icon>,
+    pub(crate) icon: Option<Icon>,
 
     // submenu fields
     hmenu: HMENU,
@@ -511,9 +519,12 @@ impl MenuChild {
             enabled,
             parents_hemnu: Vec::new(),
             internal_id,
-            id: id.unwrap_or_else(|| MenuId::new(internal_id.to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(internal_id)),
             accelerator,
             root_menu_haccel_stores: HashMap::new(),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
             icon: None,
             checked: false,
@@ -533,9 +544,12 @@ impl MenuChild {
             children: Some(Vec::new()),
             hmenu: unsafe { CreateMenu() },
             internal_id,
-            id: id.unwrap_or_else(|| MenuId::new(internal_id.to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(internal_id)),
             hpopupmenu: unsafe { CreatePopupMenu() },
             root_menu_haccel_stores: HashMap::new(),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
             icon: None,
             checked: false,
@@ -551,8 +565,11 @@ impl MenuChild {
             enabled: true,
             parents_hemnu: Vec::new(),
             internal_id,
-            id: MenuId::new(internal_id.to_string()),
+            id: MenuId::auto(internal_id),
             accelerator: item_type.accelerator(),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: Some(item_type),
             root_menu_haccel_stores: HashMap::new(),
             icon: None,
@@ -577,10 +594,13 @@ impl MenuChild {
             enabled,
             parents_hemnu: Vec::new(),
             internal_id,
-            id: id.unwrap_or_else(|| MenuId::new(internal_id.to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(internal_id)),
             accelerator,
             checked,
             root_menu_haccel_stores: HashMap::new(),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
             icon: None,
             children: None,
@@ -603,10 +623,13 @@ impl MenuChild {
             enabled,
             parents_hemnu: Vec::new(),
             internal_id,
-            id: id.unwrap_or_else(|| MenuId::new(internal_id.to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(internal_id)),
             accelerator,
             icon,
             root_menu_haccel_stores: HashMap::new(),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
             checked: false,
             children: None,
@@ -629,9 +652,12 @@ impl MenuChild {
             enabled,
             parents_hemnu: Vec::new(),
             internal_id,
-            id: id.unwrap_or_else(|| MenuId::new(internal_id.to_string())),
+            id: id.unwrap_or_else(|| MenuId::auto(internal_id)),
             accelerator,
             root_menu_haccel_stores: HashMap::new(),
+            tooltip: None,
+            data: None,
+            parents: Vec::new(),
             predefined_item_type: None,
             icon: None,
             checked: false,
@@ -640,6 +666,18 @@ impl MenuChild {
             hpopupmenu: std::ptr::null_mut(),
         }
     }
+
+    /// Creates the icon menu item of [`MenuChild::duplicate`],
+    /// native and animated icons aren't supported so only the icon is copied.
+    pub(crate) fn duplicate_icon(&self, id: Option<MenuId>) -> Self {
+        Self::new_icon(
+            &self.text,
+            self.enabled,
+            self.icon.clone(),
+            self.accelerator,
+            id,
+        )
+    }
 }
 
 /// Shared methods
@@ -709,6 +747,22 @@ impl MenuChild {
         }
     }
 
+    pub fn tooltip(&self) -> Option<String> {
+        self.tooltip.clone()
+    }
+
+    pub fn set_tooltip(&mut self, tooltip: Option<&str>) {
+        self.tooltip = tooltip.map(ToString::to_string);
+    }
+
+    pub fn data(&self) -> Option<Rc<dyn Any>> {
+        self.data.clone()
+    }
+
+    pub fn set_data(&mut self, data: Option<Rc<dyn Any>>) {
+        self.data = data;
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.parents_hemnu
             .first()
@@ -958,7 +1012,9 @@ impl MenuChild {
         hwnd: isize,
         position: Option<Position>,
     ) -> bool {
-        let rc = show_context_menu(hwnd as _, self.hpopupmenu, position);
+        let rc = show_context_menu(hwnd as _, self.hpopupmenu, position, &|id| {
+            self.find_by_id(id)
+        });
         if let Some(item) = rc.and_then(|rc| self.find_by_id(rc)) {
             unsafe {
                 menu_selected(hwnd as _, &mut item.borrow_mut());
@@ -1014,6 +1070,7 @@ unsafe fn show_context_menu(
     hwnd: windows_sys::Win32::Foundation::HWND,
     hmenu: HMENU,
     position: Option<Position>,
+    find: FindItem,
 ) -> Option<u32> {
     let result = unsafe {
         let pt = if let Some(pos) = position {
@@ -1032,7 +1089,14 @@ unsafe fn show_context_menu(
             pt
         };
         SetForegroundWindow(hwnd);
-        TrackPopupMenu(
+        // `TrackPopupMenu` doesn't return until the menu is closed, so `find` outlives the subclass
+        SetWindowSubclass(
+            hwnd,
+            Some(context_menu_subclass_proc),
+            CONTEXT_MENU_SUBCLASS_ID,
+            dwrefdata_from_obj(&find),
+        );
+        let result = TrackPopupMenu(
             hmenu,
             TPM_LEFTALIGN | TPM_RETURNCMD,
             pt.x,
@@ -1040,7 +1104,13 @@ unsafe fn show_context_menu(
             0,
             hwnd,
             std::ptr::null(),
-        )
+        );
+        RemoveWindowSubclass(
+            hwnd,
+            Some(context_menu_subclass_proc),
+            CONTEXT_MENU_SUBCLASS_ID,
+        );
+        result
     };
     (result > 0).then_some(result.try_into().ok()).flatten()
 }
@@ -1093,6 +1163,10 @@ unsafe fn obj_from_dwrefdata<T>(dwrefdata: usize) -> &'static mut T {
 const MENU_SUBCLASS_ID: usize = 200;
 const MENU_UPDATE_THEME: u32 = 201;
 const SUBMENU_SUBCLASS_ID: usize = 202;
+const CONTEXT_MENU_SUBCLASS_ID: usize = 203;
+
+/// Finds an item of the menu that is shown by its internal id.
+type FindItem<'a> = &'a dyn Fn(u32) -> Option<Rc<RefCell<MenuChild>>>;
 
 unsafe extern "system" fn menu_subclass_proc(
     hwnd: windows_sys::Win32::Foundation::HWND,
@@ -1140,6 +1214,12 @@ unsafe extern "system" fn menu_subclass_proc(
             }
         }
 
+        WM_MENUSELECT if uidsubclass == MENU_SUBCLASS_ID => {
+            let menu = obj_from_dwrefdata::<Menu>(dwrefdata);
+            menu_highlighted(wparam, lparam, &|id| menu.find_by_id(id));
+            DefSubclassProc(hwnd as _, msg, wparam, lparam)
+        }
+
         WM_UAHDRAWMENUITEM | WM_UAHDRAWMENU if uidsubclass == MENU_SUBCLASS_ID => {
             let menu = obj_from_dwrefdata::<Menu>(dwrefdata);
             let theme = menu
@@ -1178,6 +1258,52 @@ unsafe extern "system" fn menu_subclass_proc(
     }
 }
 
+/// Sends the highlight events of a context menu while it is shown.
+///
+/// The message isn't passed on, so the menu bar of the window doesn't send them again.
+unsafe extern "system" fn context_menu_subclass_proc(
+    hwnd: windows_sys::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _uidsubclass: usize,
+    dwrefdata: usize,
+) -> LRESULT {
+    if msg == WM_MENUSELECT {
+        let find = obj_from_dwrefdata::<FindItem>(dwrefdata);
+        menu_highlighted(wparam, lparam, *find);
+        return 0;
+    }
+
+    DefSubclassProc(hwnd as _, msg, wparam, lparam)
+}
+
+/// Sends a [`MenuHighlightEvent`] for the item of a `WM_MENUSELECT` message.
+unsafe fn menu_highlighted(wparam: WPARAM, lparam: LPARAM, find: FindItem) {
+    let hmenu = lparam as HMENU;
+    // the menu was closed
+    if hmenu.is_null() {
+        return;
+    }
+
+    let flags = util::HIWORD(wparam as _) as u32;
+    let position = util::LOWORD(wparam as _);
+    // submenus are identified by their position, and added with their menu handle as id
+    let id = if flags & MF_POPUP != 0 {
+        GetSubMenu(hmenu, position as _) as u32
+    } else {
+        position as u32
+    };
+
+    if let Some(item) = find(id) {
+        let item = item.borrow();
+        MenuHighlightEvent::send(MenuHighlightEvent {
+            id: item.id().clone(),
+            tooltip: item.tooltip(),
+        });
+    }
+}
+
 unsafe fn menu_selected(hwnd: windows_sys::Win32::Foundation::HWND, item: &mut MenuChild) {
     let (mut dispatch, mut menu_id) = (true, None);
 
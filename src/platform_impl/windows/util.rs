@@ -31,6 +31,11 @@ pub fn LOWORD(dword: u32) -> u16 {
     (dword & 0xFFFF) as u16
 }
 
+#[allow(non_snake_case)]
+pub fn HIWORD(dword: u32) -> u16 {
+    ((dword >> 16) & 0xFFFF) as u16
+}
+
 pub fn decode_wide(w_str: *mut u16) -> String {
     let len = unsafe { windows_sys::Win32::Globalization::lstrlenW(w_str) } as usize;
     let w_str_slice = unsafe { std::slice::from_raw_parts(w_str, len) };